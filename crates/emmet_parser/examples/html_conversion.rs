@@ -17,7 +17,7 @@ fn main() {
         ("Sibling elements", "div+p+span"),
         ("Self-closing tag", "img[src=image.jpg][alt=Image]"),
         ("Complex form", "form#login>input[type=email][placeholder=Email]+input[type=password][placeholder=Password]+button[type=submit]{Login}"),
-        ("Table structure", "table>thead>tr>th*3{Header}+tbody>tr*2>td*3{Cell}"),
+        ("Table structure", "table>thead>tr>th*3{Header}^^tbody>tr*2>td*3{Cell}"),
     ];
 
     for (description, emmet) in examples {
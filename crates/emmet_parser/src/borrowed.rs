@@ -0,0 +1,190 @@
+use std::borrow::Cow;
+
+use crate::{Attribute, AttributeSpans, ElementKind, ElementSpans, EmmetElement, Span};
+
+/// An [`EmmetElement`] whose strings borrow from the abbreviation and the
+/// snippets it was parsed with, returned by
+/// [`EmmetParser::parse_borrowed`](crate::EmmetParser::parse_borrowed).
+///
+/// Only strings that differ from what was written are allocated, such as
+/// quoted values with escaped quotes or text with substituted variables.
+///
+/// ```
+/// use std::borrow::Cow;
+/// use emmet_parser::EmmetParser;
+///
+/// let input = String::from("ul>li.item{${lang}}");
+/// let elements = EmmetParser::new(&input).parse_borrowed().unwrap();
+/// let li = &elements[0].children[0];
+/// assert!(matches!(li.classes[0], Cow::Borrowed("item")));
+/// assert!(matches!(li.text, Some(Cow::Owned(_))));
+/// assert_eq!(elements[0].clone().into_owned().to_html(), "<ul><li class=\"item\">en</li></ul>");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BorrowedElement<'a> {
+    pub kind: ElementKind,
+    pub tag: Cow<'a, str>,
+    pub id: Option<Cow<'a, str>>,
+    pub classes: Vec<Cow<'a, str>>,
+    pub attributes: Vec<BorrowedAttribute<'a>>,
+    pub text: Option<Cow<'a, str>>,
+    pub children: Vec<BorrowedElement<'a>>,
+    pub multiplier: Option<u32>,
+    /// Set by a trailing `/`, as in `br/`.
+    pub self_closing: bool,
+    /// The element and its children in the abbreviation.
+    pub span: Span,
+    pub spans: ElementSpans,
+}
+
+/// An [`Attribute`] whose name and value borrow from the abbreviation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BorrowedAttribute<'a> {
+    pub name: Cow<'a, str>,
+    pub value: Option<Cow<'a, str>>,
+    /// Set by a trailing `.`, as in `[disabled.]`.
+    pub boolean: bool,
+    /// The whole `name=value` pair in the abbreviation.
+    pub span: Span,
+    pub spans: AttributeSpans,
+}
+
+impl<'a> BorrowedElement<'a> {
+    pub fn new(tag: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            kind: ElementKind::Element,
+            tag: tag.into(),
+            id: None,
+            classes: Vec::new(),
+            attributes: Vec::new(),
+            text: None,
+            children: Vec::new(),
+            multiplier: None,
+            self_closing: false,
+            span: Span::default(),
+            spans: ElementSpans::default(),
+        }
+    }
+
+    pub fn group(children: Vec<BorrowedElement<'a>>) -> Self {
+        Self {
+            kind: ElementKind::Group,
+            children,
+            ..Self::new("")
+        }
+    }
+
+    pub fn is_group(&self) -> bool {
+        self.kind == ElementKind::Group
+    }
+
+    pub fn is_text(&self) -> bool {
+        self.kind == ElementKind::Text
+    }
+
+    /// Converts the element and its descendants into owned elements.
+    pub fn into_owned(self) -> EmmetElement {
+        EmmetElement {
+            kind: self.kind,
+            tag: self.tag.into_owned(),
+            id: self.id.map(Cow::into_owned),
+            classes: self.classes.into_iter().map(Cow::into_owned).collect(),
+            attributes: self
+                .attributes
+                .into_iter()
+                .map(BorrowedAttribute::into_owned)
+                .collect(),
+            text: self.text.map(Cow::into_owned),
+            children: self
+                .children
+                .into_iter()
+                .map(BorrowedElement::into_owned)
+                .collect(),
+            multiplier: self.multiplier,
+            self_closing: self.self_closing,
+            span: self.span,
+            spans: self.spans,
+        }
+    }
+
+    /// Applies `map` to every string of the element and its descendants
+    /// that may hold editor fields, replacing those it returns a new value
+    /// for.
+    pub(crate) fn map_values(&mut self, map: &mut dyn FnMut(&str) -> Option<String>) {
+        let values = self
+            .id
+            .iter_mut()
+            .chain(&mut self.classes)
+            .chain(self.attributes.iter_mut().filter_map(|a| a.value.as_mut()))
+            .chain(&mut self.text);
+        for value in values {
+            if let Some(mapped) = map(value) {
+                *value = Cow::Owned(mapped);
+            }
+        }
+        for child in &mut self.children {
+            child.map_values(map);
+        }
+    }
+
+    /// Points every span of the element and its descendants at `span`.
+    pub(crate) fn relocate(&mut self, span: Span) {
+        self.span = span;
+        let spans = &mut self.spans;
+        for part in [
+            &mut spans.tag,
+            &mut spans.id,
+            &mut spans.text,
+            &mut spans.multiplier,
+            &mut spans.operator,
+        ] {
+            if part.is_some() {
+                *part = Some(span);
+            }
+        }
+        spans.classes.fill(span);
+        for attr in &mut self.attributes {
+            attr.span = span;
+            attr.spans.name = span;
+            if attr.spans.value.is_some() {
+                attr.spans.value = Some(span);
+            }
+        }
+        for child in &mut self.children {
+            child.relocate(span);
+        }
+    }
+
+    /// Returns the last node at the deepest level, where children written
+    /// after an expanded snippet are inserted.
+    pub(crate) fn deepest_last_mut(&mut self) -> &mut BorrowedElement<'a> {
+        if self.children.is_empty() {
+            return self;
+        }
+        self.children.last_mut().unwrap().deepest_last_mut()
+    }
+}
+
+impl BorrowedAttribute<'_> {
+    pub fn into_owned(self) -> Attribute {
+        Attribute {
+            name: self.name.into_owned(),
+            value: self.value.map(Cow::into_owned),
+            boolean: self.boolean,
+            span: self.span,
+            spans: self.spans,
+        }
+    }
+}
+
+impl From<BorrowedElement<'_>> for EmmetElement {
+    fn from(element: BorrowedElement<'_>) -> Self {
+        element.into_owned()
+    }
+}
+
+impl From<BorrowedAttribute<'_>> for Attribute {
+    fn from(attribute: BorrowedAttribute<'_>) -> Self {
+        attribute.into_owned()
+    }
+}
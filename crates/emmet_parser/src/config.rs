@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::fields::{field_end, find_field};
+use crate::EmmetError;
+
+/// Variables every configuration starts with.
+const DEFAULT_VARIABLES: &[(&str, &str)] =
+    &[("lang", "en"), ("locale", "en-US"), ("charset", "UTF-8")];
+
+/// Settings applied while expanding an abbreviation.
+///
+/// Variables are referenced as `${name}` in snippets, text and attribute
+/// values. Numbered editor fields such as `${1}` are not variables and are
+/// left alone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpandConfig {
+    variables: HashMap<String, String>,
+    strict: bool,
+}
+
+impl Default for ExpandConfig {
+    fn default() -> Self {
+        Self {
+            variables: DEFAULT_VARIABLES
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            strict: false,
+        }
+    }
+}
+
+impl ExpandConfig {
+    /// Creates a configuration with the default variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a variable, replacing any existing value.
+    pub fn with_variable(mut self, name: &str, value: &str) -> Self {
+        self.set_variable(name, value);
+        self
+    }
+
+    /// Sets every variable yielded by `variables`.
+    pub fn with_variables<'a>(
+        mut self,
+        variables: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        for (name, value) in variables {
+            self.set_variable(name, value);
+        }
+        self
+    }
+
+    /// Reports references to unknown variables as
+    /// [`EmmetError::UnknownVariable`] instead of keeping them verbatim.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn set_variable(&mut self, name: &str, value: &str) {
+        self.variables.insert(name.to_string(), value.to_string());
+    }
+
+    pub fn variable(&self, name: &str) -> Option<&str> {
+        self.variables.get(name).map(String::as_str)
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Replaces every `${name}` variable reference in `input`, including
+    /// those inside field placeholders such as `${1:${lang}}`. References
+    /// escaped as `\${name}` are kept.
+    pub(crate) fn substitute(&self, input: &str) -> Result<String, EmmetError> {
+        let mut output = String::with_capacity(input.len());
+        let mut rest = input;
+
+        while let Some(start) = find_field(rest) {
+            output.push_str(&rest[..start]);
+            let body_start = start + 2;
+
+            let Some(len) = field_end(&rest[body_start..]) else {
+                output.push_str(&rest[start..]);
+                return Ok(output);
+            };
+            let body = &rest[body_start..body_start + len];
+
+            if is_variable_name(body) {
+                match self.variable(body) {
+                    Some(value) => output.push_str(value),
+                    None if self.strict => {
+                        return Err(EmmetError::UnknownVariable(body.to_string()));
+                    }
+                    None => output.push_str(&rest[start..body_start + len + 1]),
+                }
+            } else {
+                output.push_str("${");
+                output.push_str(&self.substitute(body)?);
+                output.push('}');
+            }
+
+            rest = &rest[body_start + len + 1..];
+        }
+
+        output.push_str(rest);
+        Ok(output)
+    }
+}
+
+/// Variable names start with a letter or `_`, which sets them apart from
+/// numbered fields.
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|ch| ch.is_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_alphanumeric() || ch == '_' || ch == '-')
+}
+
+/// Shared default configuration used when none is given.
+pub(crate) fn default_config() -> &'static ExpandConfig {
+    static DEFAULT: OnceLock<ExpandConfig> = OnceLock::new();
+    DEFAULT.get_or_init(ExpandConfig::default)
+}
@@ -0,0 +1,100 @@
+use crate::Span;
+
+/// A position in the input, in bytes and in characters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Offset {
+    pub(crate) byte: usize,
+    pub(crate) char: usize,
+}
+
+/// Reads a string one character at a time, keeping byte offsets for
+/// slicing and character offsets for spans in step, so that moving over
+/// each character takes constant time.
+pub(crate) struct Cursor<'a> {
+    input: &'a str,
+    offset: Offset,
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            offset: Offset::default(),
+        }
+    }
+
+    pub(crate) fn offset(&self) -> Offset {
+        self.offset
+    }
+
+    /// The input after the current position.
+    pub(crate) fn rest(&self) -> &'a str {
+        &self.input[self.offset.byte..]
+    }
+
+    pub(crate) fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// Returns the character after the next one.
+    pub(crate) fn peek_second(&self) -> Option<char> {
+        self.rest().chars().nth(1)
+    }
+
+    pub(crate) fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.offset.byte += ch.len_utf8();
+        self.offset.char += 1;
+        Some(ch)
+    }
+
+    pub(crate) fn eat_while(&mut self, mut predicate: impl FnMut(char) -> bool) {
+        while self.peek().is_some_and(&mut predicate) {
+            self.bump();
+        }
+    }
+
+    /// Returns the span from `start` to the current position.
+    pub(crate) fn span_from(&self, start: Offset) -> Span {
+        span(start, self.offset)
+    }
+}
+
+/// Returns the character of `input` at `at` with its span, or `None` with an
+/// empty span at the end of input.
+pub(crate) fn char_at(input: &str, at: Offset) -> (Option<char>, Span) {
+    let found = input[at.byte..].chars().next();
+    let end = match found {
+        Some(ch) => Offset {
+            byte: at.byte + ch.len_utf8(),
+            char: at.char + 1,
+        },
+        None => at,
+    };
+    (found, span(at, end))
+}
+
+/// Returns the position where `span` starts.
+pub(crate) fn offset(span: Span) -> Offset {
+    Offset {
+        byte: span.start,
+        char: span.char_start,
+    }
+}
+
+/// Returns the position where `span` ends.
+pub(crate) fn end_offset(span: Span) -> Offset {
+    Offset {
+        byte: span.end,
+        char: span.char_end,
+    }
+}
+
+pub(crate) fn span(start: Offset, end: Offset) -> Span {
+    Span {
+        start: start.byte,
+        end: end.byte,
+        char_start: start.char,
+        char_end: end.char,
+    }
+}
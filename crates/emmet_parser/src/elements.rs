@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Elements without content or closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "basefont", "br", "col", "command", "embed", "frame", "hr", "img", "input",
+    "isindex", "keygen", "link", "meta", "param", "source", "track", "wbr",
+];
+
+/// Elements that flow within text rather than forming a block.
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "acronym", "applet", "b", "basefont", "bdo", "big", "br", "button", "cite",
+    "code", "del", "dfn", "em", "font", "i", "iframe", "img", "input", "ins", "kbd", "label",
+    "object", "q", "s", "samp", "select", "small", "span", "strike", "strong", "sub", "sup",
+    "textarea", "tt", "u", "var",
+];
+
+/// Tags of children written without a tag name, by parent tag.
+const IMPLICIT_CHILDREN: &[(&str, &str)] = &[
+    ("ul", "li"),
+    ("ol", "li"),
+    ("table", "tr"),
+    ("tbody", "tr"),
+    ("thead", "tr"),
+    ("tfoot", "tr"),
+    ("tr", "td"),
+    ("select", "option"),
+    ("optgroup", "option"),
+    ("audio", "source"),
+    ("video", "source"),
+    ("map", "area"),
+    ("colgroup", "col"),
+];
+
+/// What the parser and renderer know about an element.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ElementInfo {
+    /// Has no content or closing tag, like `br`.
+    pub void: bool,
+    /// Flows within text, like `span`, rather than forming a block.
+    pub inline: bool,
+    /// Attributes rendered, in order, when the element does not set them.
+    pub default_attributes: Vec<(String, String)>,
+    /// Tag of children written without a tag name, like `li` in `ul`.
+    pub implicit_child: Option<String>,
+}
+
+impl ElementInfo {
+    /// A block element with content.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn void(mut self) -> Self {
+        self.void = true;
+        self
+    }
+
+    pub fn inline(mut self) -> Self {
+        self.inline = true;
+        self
+    }
+
+    pub fn with_default_attribute(mut self, name: &str, value: &str) -> Self {
+        self.default_attributes
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_implicit_child(mut self, tag: &str) -> Self {
+        self.implicit_child = Some(tag.to_string());
+        self
+    }
+}
+
+/// Element metadata by tag name, consulted to resolve implicit tag names,
+/// close void elements and keep inline elements together when formatting.
+///
+/// Tags are matched case-insensitively, and tags missing from the table are
+/// block elements with content.
+///
+/// ```
+/// use emmet_parser::{ElementInfo, ElementTable, EmmetParser, Renderer};
+///
+/// let mut elements = ElementTable::html();
+/// elements.insert("x-list", ElementInfo::new().with_implicit_child("x-item"));
+/// elements.insert("x-icon", ElementInfo::new().void().inline());
+///
+/// let mut parser = EmmetParser::new("x-list>.a>x-icon").with_elements(&elements);
+/// let html = Renderer::new()
+///     .with_elements(&elements)
+///     .render(&parser.parse().unwrap());
+/// assert_eq!(html, "<x-list><x-item class=\"a\"><x-icon /></x-item></x-list>");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ElementTable {
+    elements: HashMap<String, ElementInfo>,
+}
+
+impl ElementTable {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a table describing the HTML elements.
+    pub fn html() -> Self {
+        let mut table = Self::new();
+        for tag in VOID_ELEMENTS {
+            table.entry(tag).void = true;
+        }
+        for tag in INLINE_ELEMENTS {
+            table.entry(tag).inline = true;
+        }
+        for (tag, child) in IMPLICIT_CHILDREN {
+            table.entry(tag).implicit_child = Some(child.to_string());
+        }
+        table
+    }
+
+    fn entry(&mut self, tag: &str) -> &mut ElementInfo {
+        self.elements.entry(tag.to_ascii_lowercase()).or_default()
+    }
+
+    /// Describes an element, replacing what was known about it.
+    pub fn insert(&mut self, tag: &str, info: ElementInfo) {
+        *self.entry(tag) = info;
+    }
+
+    /// Removes an element, returning what was known about it.
+    pub fn remove(&mut self, tag: &str) -> Option<ElementInfo> {
+        self.elements.remove(&tag.to_ascii_lowercase())
+    }
+
+    pub fn get(&self, tag: &str) -> Option<&ElementInfo> {
+        self.elements.get(&tag.to_ascii_lowercase())
+    }
+
+    pub fn is_void(&self, tag: &str) -> bool {
+        self.get(tag).is_some_and(|info| info.void)
+    }
+
+    pub fn is_inline(&self, tag: &str) -> bool {
+        self.get(tag).is_some_and(|info| info.inline)
+    }
+
+    pub fn default_attributes(&self, tag: &str) -> &[(String, String)] {
+        self.get(tag)
+            .map_or(&[], |info| info.default_attributes.as_slice())
+    }
+
+    /// Returns the tag used for an element written without a tag name, such
+    /// as `.item`, given the tag of its parent (`None` at the top level):
+    /// the parent's implicit child, `span` inside inline elements, or `div`.
+    pub fn implicit_tag(&self, parent: Option<&str>) -> &str {
+        let Some(info) = parent.and_then(|parent| self.get(parent)) else {
+            return "div";
+        };
+
+        match &info.implicit_child {
+            Some(child) => child,
+            None if info.inline => "span",
+            None => "div",
+        }
+    }
+}
+
+/// Shared instance of the HTML element table used by default.
+pub(crate) fn builtin_elements() -> &'static ElementTable {
+    static HTML: OnceLock<ElementTable> = OnceLock::new();
+    HTML.get_or_init(ElementTable::html)
+}
+
+/// Returns the tag used for an element written without a tag name, such as
+/// `.item`, given the tag of its parent (`None` at the top level).
+///
+/// ```
+/// use emmet_parser::implicit_tag;
+///
+/// assert_eq!(implicit_tag(Some("ul")), "li");
+/// assert_eq!(implicit_tag(Some("em")), "span");
+/// assert_eq!(implicit_tag(None), "div");
+/// ```
+pub fn implicit_tag(parent: Option<&str>) -> &'static str {
+    builtin_elements().implicit_tag(parent)
+}
@@ -0,0 +1,271 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Span;
+
+/// Stable identifier of a kind of parse error, for tools that match on or
+/// document errors. Codes keep their meaning across releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "&str", try_from = "String")
+)]
+pub enum ErrorCode {
+    /// No element could start here, as in `div>@`.
+    ExpectedElement,
+    /// An element is followed by something other than an operator, as in
+    /// `(p)(em)`.
+    UnexpectedCharacter,
+    /// A `)` without a matching `(`.
+    UnmatchedParen,
+    /// A `(` without a matching `)`.
+    UnclosedGroup,
+    /// A group followed by `>`, which groups cannot take.
+    GroupChildren,
+    /// An id or attribute name is missing, as in `div#` or `div[=x]`.
+    ExpectedName,
+    /// A `.` not followed by a class name.
+    ExpectedClassName,
+    /// A `*` not followed by a count, or a count or `$@` start number that
+    /// is too large.
+    InvalidNumber,
+    /// A `{` without a matching `}`.
+    UnclosedText,
+    /// A quoted attribute value without its closing quote.
+    UnclosedQuote,
+    /// An `=` not followed by an attribute value.
+    ExpectedAttributeValue,
+    /// A `|` not followed by a filter name.
+    ExpectedFilter,
+    /// A snippet named in the abbreviation failed to parse.
+    InvalidSnippet,
+    /// A `${name}` variable that is not defined, in strict mode. Only
+    /// reported as a diagnostic of [`EmmetParser::parse_partial`](crate::EmmetParser::parse_partial).
+    UnknownVariable,
+    /// An element with a second multiplier, as in `p*2*3`.
+    RepeatedMultiplier,
+    /// Children or groups nested more than 128 levels deep.
+    NestingTooDeep,
+}
+
+impl ErrorCode {
+    /// Every code, in order.
+    pub const ALL: [ErrorCode; 16] = [
+        ErrorCode::ExpectedElement,
+        ErrorCode::UnexpectedCharacter,
+        ErrorCode::UnmatchedParen,
+        ErrorCode::UnclosedGroup,
+        ErrorCode::GroupChildren,
+        ErrorCode::ExpectedName,
+        ErrorCode::ExpectedClassName,
+        ErrorCode::InvalidNumber,
+        ErrorCode::UnclosedText,
+        ErrorCode::UnclosedQuote,
+        ErrorCode::ExpectedAttributeValue,
+        ErrorCode::ExpectedFilter,
+        ErrorCode::InvalidSnippet,
+        ErrorCode::UnknownVariable,
+        ErrorCode::RepeatedMultiplier,
+        ErrorCode::NestingTooDeep,
+    ];
+
+    /// Returns the code as written in diagnostics, such as `E006`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::ExpectedElement => "E001",
+            ErrorCode::UnexpectedCharacter => "E002",
+            ErrorCode::UnmatchedParen => "E003",
+            ErrorCode::UnclosedGroup => "E004",
+            ErrorCode::GroupChildren => "E005",
+            ErrorCode::ExpectedName => "E006",
+            ErrorCode::ExpectedClassName => "E007",
+            ErrorCode::InvalidNumber => "E008",
+            ErrorCode::UnclosedText => "E009",
+            ErrorCode::UnclosedQuote => "E010",
+            ErrorCode::ExpectedAttributeValue => "E011",
+            ErrorCode::ExpectedFilter => "E012",
+            ErrorCode::InvalidSnippet => "E013",
+            ErrorCode::UnknownVariable => "E014",
+            ErrorCode::RepeatedMultiplier => "E015",
+            ErrorCode::NestingTooDeep => "E016",
+        }
+    }
+}
+
+impl From<ErrorCode> for &'static str {
+    fn from(code: ErrorCode) -> Self {
+        code.as_str()
+    }
+}
+
+impl FromStr for ErrorCode {
+    type Err = String;
+
+    /// Parses a code as written by [`ErrorCode::as_str`].
+    fn from_str(code: &str) -> Result<Self, String> {
+        ErrorCode::ALL
+            .into_iter()
+            .find(|known| known.as_str() == code)
+            .ok_or_else(|| format!("unknown error code '{}'", code))
+    }
+}
+
+impl TryFrom<String> for ErrorCode {
+    type Error = String;
+
+    fn try_from(code: String) -> Result<Self, String> {
+        code.parse()
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A kind of token that the parser accepts at some point of the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Expected {
+    /// The start of an element: a tag name, `#`, `.`, `[`, `{` or `(`.
+    Element,
+    /// More characters of the tag name being written.
+    TagName,
+    Id,
+    ClassName,
+    AttributeName,
+    AttributeValue,
+    Text,
+    /// A multiplier count.
+    Number,
+    FilterName,
+    /// A specific character, such as an operator or a closing bracket.
+    Char(char),
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Element => f.write_str("element"),
+            Expected::TagName => f.write_str("tag name"),
+            Expected::Id => f.write_str("id"),
+            Expected::ClassName => f.write_str("class name"),
+            Expected::AttributeName => f.write_str("attribute name"),
+            Expected::AttributeValue => f.write_str("attribute value"),
+            Expected::Text => f.write_str("text"),
+            Expected::Number => f.write_str("number"),
+            Expected::FilterName => f.write_str("filter name"),
+            Expected::Char(ch) => write!(f, "'{}'", ch),
+        }
+    }
+}
+
+/// Where and why an abbreviation failed to parse.
+///
+/// ```
+/// use emmet_parser::{emmet_to_html, ErrorCode};
+///
+/// let err = emmet_to_html("div[=value]").unwrap_err();
+/// let parse_error = err.parse_error().unwrap();
+/// assert_eq!(parse_error.code, ErrorCode::ExpectedName);
+/// assert_eq!(parse_error.found, Some('='));
+/// assert_eq!(
+///     err.render("div[=value]"),
+///     "error[E006]: expected attribute name or ']', found '='\n  |\n1 | div[=value]\n  |     ^"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseError {
+    pub code: ErrorCode,
+    /// The offending part of the abbreviation: the unexpected character,
+    /// the bracket left open, or an empty span at the end of input.
+    pub span: Span,
+    /// What would have been valid instead.
+    pub expected: Vec<Expected>,
+    /// The character found instead, or `None` at the end of input.
+    pub found: Option<char>,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Creates an error with a message listing what was expected and found.
+    pub(crate) fn new(
+        code: ErrorCode,
+        span: Span,
+        expected: &[Expected],
+        found: Option<char>,
+    ) -> Self {
+        let found_text = match found {
+            Some(ch) => format!("'{}'", ch),
+            None => "end of input".to_string(),
+        };
+        let message = match expected {
+            [] => format!("unexpected {}", found_text),
+            [expected] => format!("expected {}, found {}", expected, found_text),
+            [expected @ .., last] => {
+                let expected: Vec<_> = expected.iter().map(Expected::to_string).collect();
+                format!(
+                    "expected {} or {}, found {}",
+                    expected.join(", "),
+                    last,
+                    found_text
+                )
+            }
+        };
+
+        Self {
+            code,
+            span,
+            expected: expected.to_vec(),
+            found,
+            message,
+        }
+    }
+
+    pub(crate) fn with_message(mut self, message: String) -> Self {
+        self.message = message;
+        self
+    }
+
+    /// Renders the error with the line of `input` it occurred on, the span
+    /// underlined with carets:
+    ///
+    /// ```text
+    /// error[E004]: expected ')', found end of input
+    ///   |
+    /// 1 | (div>p
+    ///   | ^
+    /// ```
+    pub fn render(&self, input: &str) -> String {
+        let start = self.span.start.min(input.len());
+        let line_start = input[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[start..].find('\n').map_or(input.len(), |i| start + i);
+        let line = &input[line_start..line_end];
+        let number = input[..line_start].matches('\n').count() + 1;
+
+        let column = input[line_start..start].chars().count();
+        let end = self.span.end.clamp(start, line_end);
+        let width = input[start..end].chars().count().max(1);
+
+        let gutter = " ".repeat(number.to_string().len());
+        format!(
+            "error[{}]: {}\n{gutter} |\n{} | {}\n{gutter} | {}{}",
+            self.code,
+            self.message,
+            number,
+            line,
+            " ".repeat(column),
+            "^".repeat(width),
+        )
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.span.char_start + 1)
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+
+/// Writes an attribute value written between `quote` characters, escaped.
+pub(crate) fn write_attribute(out: &mut dyn fmt::Write, value: &str, quote: char) -> fmt::Result {
+    escape(out, value, |ch| match ch {
+        '<' => Some("&lt;"),
+        '"' if quote == '"' => Some("&quot;"),
+        '\'' if quote == '\'' => Some("&#39;"),
+        _ => None,
+    })
+}
+
+/// Writes text content, escaped.
+pub(crate) fn write_text(out: &mut dyn fmt::Write, text: &str) -> fmt::Result {
+    escape(out, text, |ch| match ch {
+        '<' => Some("&lt;"),
+        '>' => Some("&gt;"),
+        _ => None,
+    })
+}
+
+/// Writes the content of a comment, with each `>` that would end it early
+/// escaped, as in `-->`.
+pub(crate) fn write_comment(out: &mut dyn fmt::Write, comment: &str) -> fmt::Result {
+    let comment = comment.replace("-->", "--&gt;").replace("--!>", "--!&gt;");
+    // `<!-->` and `<!--->` are empty comments
+    let end = match comment.as_bytes() {
+        [b'>', ..] => 0,
+        [b'-', b'>', ..] => 1,
+        _ => return out.write_str(&comment),
+    };
+    out.write_str(&comment[..end])?;
+    out.write_str("&gt;")?;
+    out.write_str(&comment[end + 1..])
+}
+
+/// Replaces the characters `replacement` returns an entity for, and every
+/// `&` that does not already start an entity such as `&copy;` or `&#169;`.
+/// Text between them is written as is.
+fn escape(
+    out: &mut dyn fmt::Write,
+    input: &str,
+    replacement: impl Fn(char) -> Option<&'static str>,
+) -> fmt::Result {
+    let mut start = 0;
+
+    for (offset, ch) in input.char_indices() {
+        let entity = match ch {
+            '&' if !is_entity(&input[offset..]) => Some("&amp;"),
+            '&' => None,
+            _ => replacement(ch),
+        };
+        if let Some(entity) = entity {
+            out.write_str(&input[start..offset])?;
+            out.write_str(entity)?;
+            start = offset + ch.len_utf8();
+        }
+    }
+
+    out.write_str(&input[start..])
+}
+
+/// Whether `input` starts with a named or numeric character reference.
+fn is_entity(input: &str) -> bool {
+    input.strip_prefix('&').is_some_and(is_reference)
+}
+
+/// Whether `body` starts with what follows the `&` of a character
+/// reference, as in `copy;`.
+pub(crate) fn is_reference(body: &str) -> bool {
+    let Some(end) = body.find(';') else {
+        return false;
+    };
+    let name = &body[..end];
+
+    if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+        !hex.is_empty() && hex.chars().all(|ch| ch.is_ascii_hexdigit())
+    } else if let Some(decimal) = name.strip_prefix('#') {
+        !decimal.is_empty() && decimal.chars().all(|ch| ch.is_ascii_digit())
+    } else {
+        name.chars()
+            .next()
+            .is_some_and(|ch| ch.is_ascii_alphabetic())
+            && name.chars().all(|ch| ch.is_ascii_alphanumeric())
+    }
+}
@@ -0,0 +1,371 @@
+use crate::fields::{unescape, FieldScope, Fields};
+use crate::numbering::Repeat;
+use crate::{Attribute, AttributeSpans, ElementKind, ElementTable, EmmetElement, Renderer, Span};
+
+/// A node of the markup an abbreviation stands for, as returned by
+/// [`expand`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ExpandedNode {
+    Element(ExpandedElement),
+    /// Text, with editor fields written by the field formatter.
+    Text(String),
+    /// The content of a `<!--...-->` comment, as written by the `c` snippet.
+    /// A `-->` in it is escaped when rendering.
+    Comment(String),
+    /// A document type declaration, holding what follows `<!DOCTYPE`, as
+    /// in `html`.
+    Doctype(String),
+}
+
+/// An element of the expanded markup, rendered as one tag.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExpandedElement {
+    pub tag: String,
+    /// The id, the classes joined into one `class` attribute, the
+    /// attributes and then the default attributes the element does not
+    /// set, in render order. Only boolean attributes are without a value.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attributes: Vec<Attribute>,
+    /// The text content followed by the child nodes.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub children: Vec<ExpandedNode>,
+    /// Set by a trailing `/`, as in `br/`. Void elements are looked up in
+    /// the element table when rendering.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub self_closing: bool,
+    /// The element of the abbreviation this one was expanded from.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
+}
+
+impl ExpandedNode {
+    pub fn as_element(&self) -> Option<&ExpandedElement> {
+        match self {
+            ExpandedNode::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+}
+
+impl ExpandedElement {
+    /// Returns the value of attribute `name`, matched case-insensitively.
+    /// Boolean attributes give `Some("")`.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name.eq_ignore_ascii_case(name))
+            .map(|attribute| attribute.value.as_deref().unwrap_or(""))
+    }
+}
+
+/// Expands parsed elements into the concrete markup they stand for: every
+/// repetition is written out with its `$` numbering resolved, groups are
+/// replaced by their content, and ids, classes and default attributes
+/// become attributes. Editor fields from snippets are replaced by their
+/// placeholders; [`Renderer::expand`] writes them with its field formatter
+/// instead.
+///
+/// ```
+/// use emmet_parser::{expand, parse_emmet};
+///
+/// let nodes = expand(&parse_emmet("ul>li.item$*3").unwrap());
+/// let ul = nodes[0].as_element().unwrap();
+/// assert_eq!(ul.children.len(), 3);
+/// assert_eq!(ul.children[2].as_element().unwrap().attribute("class"), Some("item3"));
+/// ```
+pub fn expand(elements: &[EmmetElement]) -> Vec<ExpandedNode> {
+    Renderer::new().expand(elements)
+}
+
+/// Expands parsed elements with the default attributes of `table` instead
+/// of the HTML element table. See [`expand`].
+pub fn expand_with_elements(elements: &[EmmetElement], table: &ElementTable) -> Vec<ExpandedNode> {
+    Renderer::new().with_elements(table).expand(elements)
+}
+
+impl EmmetElement {
+    /// Expands the element and its repetitions. See [`expand`].
+    pub fn expand(&self) -> Vec<ExpandedNode> {
+        expand(std::slice::from_ref(self))
+    }
+}
+
+/// A node of the expanded markup before it is expanded: a repetition of an
+/// element, numbered by the multiplier it is in, text, or the comment or
+/// doctype of a snippet.
+#[derive(Clone, Copy)]
+pub(crate) enum Pending<'e> {
+    Element(&'e EmmetElement, Option<Repeat>),
+    Text(&'e str, Option<Repeat>),
+    Markup(&'e EmmetElement, Option<Repeat>),
+}
+
+/// The nodes a list of elements expands to, produced one at a time so that
+/// repetitions are never collected. Groups are replaced by their content,
+/// and text nodes are followed by the nodes written after them, as in
+/// `{Hi}>p`.
+#[derive(Clone)]
+pub(crate) struct Siblings<'e> {
+    /// Text produced before the elements, as the content of an element.
+    text: Option<Pending<'e>>,
+    /// The lists being walked, innermost last: the elements, then the
+    /// content of the groups and text nodes in them.
+    levels: Vec<Level<'e>>,
+}
+
+#[derive(Clone)]
+struct Level<'e> {
+    elements: std::slice::Iter<'e, EmmetElement>,
+    /// Numbers the elements without a multiplier of their own.
+    repeat: Option<Repeat>,
+    /// The element being repeated by its multiplier, with the index of its
+    /// next repetition.
+    repeating: Option<(&'e EmmetElement, u32)>,
+}
+
+impl<'e> Siblings<'e> {
+    /// The nodes `elements` expand to, numbered by `repeat` unless they
+    /// have a multiplier of their own.
+    pub fn new(elements: &'e [EmmetElement], repeat: Option<Repeat>) -> Self {
+        let mut siblings = Self {
+            text: None,
+            levels: Vec::new(),
+        };
+        siblings.enter(elements, repeat);
+        siblings
+    }
+
+    /// The child nodes of an element: its text, then its children.
+    pub fn children(element: &'e EmmetElement, repeat: Option<Repeat>) -> Self {
+        let mut children = Self::new(&element.children, repeat);
+        children.text = element
+            .text
+            .as_deref()
+            .map(|text| Pending::Text(text, repeat));
+        children
+    }
+
+    fn enter(&mut self, elements: &'e [EmmetElement], repeat: Option<Repeat>) {
+        if !elements.is_empty() {
+            self.levels.push(Level {
+                elements: elements.iter(),
+                repeat,
+                repeating: None,
+            });
+        }
+    }
+}
+
+impl<'e> Iterator for Siblings<'e> {
+    type Item = Pending<'e>;
+
+    fn next(&mut self) -> Option<Pending<'e>> {
+        if let Some(text) = self.text.take() {
+            return Some(text);
+        }
+
+        loop {
+            let level = self.levels.last_mut()?;
+            let (element, repeat) = match level.repeating {
+                Some((element, index)) => {
+                    let count = element.multiplier.unwrap_or(0);
+                    if index == count {
+                        level.repeating = None;
+                        continue;
+                    }
+                    level.repeating = Some((element, index + 1));
+                    (element, Some(Repeat { index, count }))
+                }
+                None => match level.elements.next() {
+                    Some(element) if element.multiplier.is_some() => {
+                        level.repeating = Some((element, 0));
+                        continue;
+                    }
+                    Some(element) => (element, level.repeat),
+                    None => {
+                        self.levels.pop();
+                        continue;
+                    }
+                },
+            };
+
+            match element.kind {
+                ElementKind::Group => self.enter(&element.children, repeat),
+                ElementKind::Text => {
+                    self.enter(&element.children, repeat);
+                    if let Some(text) = &element.text {
+                        return Some(Pending::Text(text, repeat));
+                    }
+                }
+                ElementKind::Comment | ElementKind::Doctype => {
+                    self.enter(&element.children, repeat);
+                    return Some(Pending::Markup(element, repeat));
+                }
+                ElementKind::Element => return Some(Pending::Element(element, repeat)),
+            }
+        }
+    }
+}
+
+/// Resolves `$` numbering against the repetition `repeat`.
+pub(crate) fn number(value: &str, repeat: Option<Repeat>) -> String {
+    match repeat {
+        Some(repeat) => repeat.apply(value),
+        None => value.to_string(),
+    }
+}
+
+/// Expands nodes in output order, numbering editor fields as they are
+/// written.
+pub(crate) struct Expander<'e> {
+    renderer: &'e Renderer<'e>,
+    fields: Fields<'e>,
+}
+
+impl<'e> Expander<'e> {
+    pub fn new(renderer: &'e Renderer<'e>) -> Self {
+        Self {
+            renderer,
+            fields: Fields::new(renderer.fields),
+        }
+    }
+
+    /// Expands `nodes` and everything in them into `expanded`. Text uses
+    /// the field `scope` of the element it is in.
+    pub fn expand(
+        &mut self,
+        nodes: Siblings,
+        mut scope: Option<&mut FieldScope>,
+        expanded: &mut Vec<ExpandedNode>,
+    ) {
+        for node in nodes {
+            match node {
+                Pending::Text(text, repeat) => {
+                    let mut own_scope = FieldScope::new();
+                    let scope = scope.as_deref_mut().unwrap_or(&mut own_scope);
+                    expanded.push(self.text(text, repeat, scope));
+                }
+                Pending::Markup(markup, repeat) => {
+                    let mut own_scope = FieldScope::new();
+                    let scope = scope.as_deref_mut().unwrap_or(&mut own_scope);
+                    expanded.push(self.markup(markup, repeat, scope));
+                }
+                Pending::Element(element, repeat) => {
+                    // Fields are shared within an element, not with its parent
+                    let mut scope = FieldScope::new();
+                    let mut node = self.element(element, repeat, &mut scope);
+                    let children = Siblings::children(element, repeat);
+                    self.expand(children, Some(&mut scope), &mut node.children);
+                    if node.children.is_empty() {
+                        if let Some(field) = self.content(&node) {
+                            node.children.push(ExpandedNode::Text(field));
+                        }
+                    }
+                    expanded.push(ExpandedNode::Element(node));
+                }
+            }
+        }
+    }
+
+    /// Expands text.
+    pub fn text(
+        &mut self,
+        text: &str,
+        repeat: Option<Repeat>,
+        scope: &mut FieldScope,
+    ) -> ExpandedNode {
+        ExpandedNode::Text(self.fields.format(&number(text, repeat), scope))
+    }
+
+    /// Expands the comment or doctype of a snippet.
+    pub fn markup(
+        &mut self,
+        markup: &EmmetElement,
+        repeat: Option<Repeat>,
+        scope: &mut FieldScope,
+    ) -> ExpandedNode {
+        let text = number(markup.text.as_deref().unwrap_or(""), repeat);
+        match markup.kind {
+            ElementKind::Doctype => ExpandedNode::Doctype(unescape(&text).into_owned()),
+            _ => ExpandedNode::Comment(self.fields.format(&text, scope)),
+        }
+    }
+
+    /// Expands a repetition of an element, without its children.
+    pub fn element(
+        &mut self,
+        element: &EmmetElement,
+        repeat: Option<Repeat>,
+        scope: &mut FieldScope,
+    ) -> ExpandedElement {
+        let attribute = |name: &str, value: String| Attribute {
+            name: name.to_string(),
+            value: Some(value),
+            boolean: false,
+            span: Span::default(),
+            spans: AttributeSpans::default(),
+        };
+
+        let mut attributes = Vec::new();
+        if let Some(id) = &element.id {
+            attributes.push(attribute("id", number(id, repeat)));
+        }
+        if !element.classes.is_empty() {
+            attributes.push(attribute(
+                "class",
+                number(&element.classes.join(" "), repeat),
+            ));
+        }
+        for attr in &element.attributes {
+            attributes.push(Attribute {
+                name: number(&attr.name, repeat),
+                value: attr.value.as_deref().map(|value| number(value, repeat)),
+                ..attr.clone()
+            });
+        }
+        for (name, default) in self.renderer.elements.default_attributes(&element.tag) {
+            let set = attributes
+                .iter()
+                .any(|attr| attr.name.eq_ignore_ascii_case(name));
+            if !set {
+                attributes.push(attribute(name, number(default, repeat)));
+            }
+        }
+
+        // Values may contain editor fields from snippets, and empty values
+        // become fields
+        for attr in &mut attributes {
+            if attr.is_boolean() {
+                continue;
+            }
+            attr.value = Some(match attr.value.as_deref() {
+                Some(value) if !value.is_empty() => self.fields.format(value, scope),
+                _ => self.fields.empty(),
+            });
+        }
+
+        ExpandedElement {
+            tag: number(&element.tag, repeat),
+            attributes,
+            children: Vec::new(),
+            self_closing: element.self_closing,
+            span: element.span,
+        }
+    }
+
+    /// The field where the content of an element without children goes,
+    /// unless the element is closed without content or the field formatter
+    /// writes nothing.
+    pub fn content(&mut self, element: &ExpandedElement) -> Option<String> {
+        if self
+            .renderer
+            .closes(&element.tag, element.self_closing, true)
+        {
+            return None;
+        }
+        Some(self.fields.empty()).filter(|field| !field.is_empty())
+    }
+}
@@ -0,0 +1,250 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Writes editor fields (tabstops and placeholders) in the syntax of an
+/// editor.
+///
+/// Fields are numbered from 1 in output order. Fields sharing a number
+/// within one element, like the `${1}` in both `name` and `id` of the `inp`
+/// snippet, keep sharing it.
+pub trait FieldFormatter {
+    /// Formats field `index`. `placeholder` is already formatted and may be
+    /// empty.
+    fn field(&self, index: usize, placeholder: &str) -> String;
+
+    /// Escapes literal text so that it is not read as field syntax.
+    fn text(&self, text: &str) -> String {
+        text.to_string()
+    }
+}
+
+/// VS Code and LSP snippet syntax: `${1}` and `${1:placeholder}`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LspFields;
+
+impl FieldFormatter for LspFields {
+    fn field(&self, index: usize, placeholder: &str) -> String {
+        if placeholder.is_empty() {
+            format!("${{{}}}", index)
+        } else {
+            format!("${{{}:{}}}", index, placeholder)
+        }
+    }
+
+    fn text(&self, text: &str) -> String {
+        escape(text, &['\\', '$', '}'])
+    }
+}
+
+/// TextMate snippet syntax: `$1` and `${1:placeholder}`. Backticks are
+/// escaped as well, since TextMate runs them as shell code.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextMateFields;
+
+impl FieldFormatter for TextMateFields {
+    fn field(&self, index: usize, placeholder: &str) -> String {
+        if placeholder.is_empty() {
+            format!("${}", index)
+        } else {
+            format!("${{{}:{}}}", index, placeholder)
+        }
+    }
+
+    fn text(&self, text: &str) -> String {
+        escape(text, &['\\', '$', '}', '`'])
+    }
+}
+
+/// Plain text: fields become their placeholder, and empty fields become
+/// `marker`, such as `|` to show where the cursor goes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlainFields {
+    marker: String,
+}
+
+impl PlainFields {
+    pub fn new(marker: &str) -> Self {
+        Self {
+            marker: marker.to_string(),
+        }
+    }
+}
+
+impl FieldFormatter for PlainFields {
+    fn field(&self, _index: usize, placeholder: &str) -> String {
+        if placeholder.is_empty() {
+            self.marker.clone()
+        } else {
+            placeholder.to_string()
+        }
+    }
+}
+
+/// Plain text without cursor markers, as used for HTML output.
+pub(crate) static NO_MARKERS: PlainFields = PlainFields {
+    marker: String::new(),
+};
+
+fn escape(text: &str, special: &[char]) -> String {
+    let mut output = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if special.contains(&ch) {
+            output.push('\\');
+        }
+        output.push(ch);
+    }
+    output
+}
+
+/// Field numbering state shared by everything rendered in one output.
+pub(crate) struct Fields<'f> {
+    formatter: &'f dyn FieldFormatter,
+    last: usize,
+}
+
+/// Maps field numbers written in one element to output field numbers.
+pub(crate) type FieldScope = HashMap<usize, usize>;
+
+impl<'f> Fields<'f> {
+    pub fn new(formatter: &'f dyn FieldFormatter) -> Self {
+        Self { formatter, last: 0 }
+    }
+
+    /// A new field without placeholder, for empty attribute values and
+    /// element bodies.
+    pub fn empty(&mut self) -> String {
+        self.last += 1;
+        self.formatter.field(self.last, "")
+    }
+
+    /// Formats the fields such as `${1}` or `${2:placeholder}` in `input`
+    /// and escapes the text around them. `\$` is a literal `$`.
+    ///
+    /// Only fields whose name is a number are fields; anything else written
+    /// as `${...}` is kept as text.
+    pub fn format(&mut self, input: &str, scope: &mut FieldScope) -> String {
+        let mut output = String::with_capacity(input.len());
+        let mut rest = input;
+
+        while let Some(start) = find_field(rest) {
+            output.push_str(&self.formatter.text(&unescape(&rest[..start])));
+            let body_start = start + 2;
+
+            let Some(len) = field_end(&rest[body_start..]) else {
+                rest = &rest[start..];
+                break;
+            };
+            let end = body_start + len + 1;
+            let body = &rest[body_start..end - 1];
+            let (index, placeholder) = match body.split_once(':') {
+                Some((index, placeholder)) => (index, placeholder),
+                None => (body, ""),
+            };
+
+            let index = Some(index)
+                .filter(|index| index.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|index| index.parse::<usize>().ok());
+
+            match index {
+                Some(index) => {
+                    let number = match scope.get(&index) {
+                        Some(&number) => number,
+                        None => {
+                            self.last += 1;
+                            scope.insert(index, self.last);
+                            self.last
+                        }
+                    };
+                    let placeholder = self.format(placeholder, scope);
+                    output.push_str(&self.formatter.field(number, &placeholder));
+                }
+                None => output.push_str(&self.formatter.text(&unescape(&rest[start..end]))),
+            }
+
+            rest = &rest[end..];
+        }
+
+        output.push_str(&self.formatter.text(&unescape(rest)));
+        output
+    }
+}
+
+/// Rewrites the number of every field in `input`, including fields nested
+/// in placeholders.
+pub(crate) fn renumber_fields(input: &str, renumber: &mut dyn FnMut(usize) -> usize) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = find_field(rest) {
+        output.push_str(&rest[..start]);
+        let body_start = start + 2;
+
+        let Some(len) = field_end(&rest[body_start..]) else {
+            rest = &rest[start..];
+            break;
+        };
+        let end = body_start + len + 1;
+        let body = &rest[body_start..end - 1];
+        let (index, placeholder) = match body.split_once(':') {
+            Some((index, placeholder)) => (index, Some(placeholder)),
+            None => (body, None),
+        };
+
+        match index.parse::<usize>() {
+            Ok(number) if index.bytes().all(|b| b.is_ascii_digit()) => {
+                output.push_str(&format!("${{{}", renumber(number)));
+                if let Some(placeholder) = placeholder {
+                    output.push(':');
+                    output.push_str(&renumber_fields(placeholder, renumber));
+                }
+                output.push('}');
+            }
+            _ => output.push_str(&rest[start..end]),
+        }
+
+        rest = &rest[end..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Returns the offset of the first `${` in `text` that is not escaped as
+/// `\${`.
+pub(crate) fn find_field(text: &str) -> Option<usize> {
+    let mut chars = text.char_indices().peekable();
+    while let Some((offset, ch)) = chars.next() {
+        match (ch, chars.peek()) {
+            ('\\', Some((_, '$'))) => {
+                chars.next();
+            }
+            ('$', Some((_, '{'))) => return Some(offset),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Replaces each `\$` in `text` with the literal `$` it stands for.
+pub(crate) fn unescape(text: &str) -> Cow<'_, str> {
+    match text.contains("\\$") {
+        true => Cow::Owned(text.replace("\\$", "$")),
+        false => Cow::Borrowed(text),
+    }
+}
+
+/// Returns the length of a field body up to its matching `}`.
+pub(crate) fn field_end(body: &str) -> Option<usize> {
+    let mut depth = 0;
+
+    for (offset, ch) in body.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(offset),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
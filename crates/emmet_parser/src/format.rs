@@ -0,0 +1,57 @@
+/// Layout of pretty-printed markup, after Emmet's `output.*` and
+/// `markup.format` options.
+///
+/// Block elements start on their own line, indented by their depth. Inline
+/// elements and text stay on the line of their siblings, unless they follow
+/// a block element, contain one, or form a run of at least `inline_break`
+/// inline siblings. Which elements are inline comes from the renderer's
+/// [`ElementTable`](crate::ElementTable), plus `inline_elements`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatOptions {
+    /// One level of indentation.
+    pub indent: String,
+    pub newline: String,
+    /// Prepended to every line but the first, for output inserted at a
+    /// column.
+    pub base_indent: String,
+    /// Tags kept on the line of their siblings, in addition to the inline
+    /// elements of the element table.
+    pub inline_elements: Vec<String>,
+    /// Number of adjacent inline siblings from which each goes on its own
+    /// line; 0 keeps them together however many there are.
+    pub inline_break: usize,
+    /// Tags whose content is always indented on its own lines, even when
+    /// empty or inline.
+    pub force_indent: Vec<String>,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: "\t".to_string(),
+            newline: "\n".to_string(),
+            base_indent: String::new(),
+            inline_elements: Vec::new(),
+            inline_break: 3,
+            force_indent: vec!["body".to_string()],
+        }
+    }
+}
+
+impl FormatOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn is_inline(&self, tag: &str) -> bool {
+        self.inline_elements
+            .iter()
+            .any(|inline| inline.eq_ignore_ascii_case(tag))
+    }
+
+    pub(crate) fn forces_indent(&self, tag: &str) -> bool {
+        self.force_indent
+            .iter()
+            .any(|forced| forced.eq_ignore_ascii_case(tag))
+    }
+}
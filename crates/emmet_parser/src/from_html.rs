@@ -0,0 +1,845 @@
+use crate::escape::is_reference;
+use crate::{
+    markup, to_abbreviation, Attribute, AttributeSpans, ElementKind, EmmetElement, Span,
+    BOOLEAN_ATTRIBUTES,
+};
+
+/// Converts an HTML fragment into an abbreviation, for storing existing
+/// markup as a snippet. See [`html_to_elements`].
+///
+/// ```
+/// use emmet_parser::html_to_abbreviation;
+///
+/// let html = r#"<ul class="nav"><li id="item1">One<li id="item2">Two</ul>"#;
+/// assert_eq!(html_to_abbreviation(html), "ul.nav>#item1{One}+#item2{Two}");
+///
+/// let html = "<ul><li class=\"item1\"></li><li class=\"item2\"></li><li class=\"item3\"></li></ul>";
+/// assert_eq!(html_to_abbreviation(html), "ul>.item$*3");
+/// ```
+pub fn html_to_abbreviation(html: &str) -> String {
+    to_abbreviation(&html_to_elements(html))
+}
+
+/// Parses an HTML fragment into elements that render back to it.
+///
+/// The abbreviation of the elements renders the same HTML when parsed
+/// without snippets, as by `EmmetParser::without_snippets`. With the
+/// default snippets, as by [`emmet_to_html`](crate::emmet_to_html), tags
+/// named like a snippet expand to it: `<a>x</a>` becomes `a{x}`, which
+/// renders `<a href="">x</a>`.
+///
+/// The HTML does not need to be well-formed: unclosed elements end with
+/// their parent, and elements such as `li` and `p` end where the next one
+/// starts. Runs of identical siblings become one element with a
+/// multiplier, and names, values and text that count up or down across
+/// the run, like `item1`, `item2`, become `$` numbering.
+///
+/// `id` and `class` attributes become the element's id and classes.
+/// Comments and the doctype become comment and doctype nodes, and other
+/// `<!...>` declarations text nodes. Tags whose names cannot be written in
+/// an abbreviation, such as `<my$tag>`, are read as text. Attributes whose
+/// names cannot be written, such as `@click`, are left out. Literal `$` in
+/// text and values is escaped as `\$`, and whitespace is collapsed outside
+/// of `pre`, `textarea`, `script` and `style`.
+pub fn html_to_elements(html: &str) -> Vec<EmmetElement> {
+    let mut nodes = HtmlReader::new(html).read();
+    normalize(&mut nodes, false);
+    compress(nodes)
+        .into_iter()
+        .map(Node::into_element)
+        .collect()
+}
+
+/// Elements whose content is read as text up to their closing tag.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "textarea", "title"];
+
+/// Elements whose whitespace is kept.
+const PREFORMATTED_ELEMENTS: &[&str] = &["pre", "textarea", "script", "style"];
+
+/// Elements that a start tag ends when they are open, as in
+/// `<li>One<li>Two`.
+const IMPLIED_END_TAGS: &[(&str, &[&str])] = &[
+    ("li", &["li"]),
+    ("dt", &["dt", "dd"]),
+    ("dd", &["dt", "dd"]),
+    ("tr", &["tr", "td", "th"]),
+    ("td", &["td", "th"]),
+    ("th", &["td", "th"]),
+    ("option", &["option"]),
+    ("p", &["p"]),
+    ("div", &["p"]),
+    ("ul", &["p"]),
+    ("ol", &["p"]),
+    ("table", &["p"]),
+    ("h1", &["p"]),
+    ("h2", &["p"]),
+    ("h3", &["p"]),
+    ("h4", &["p"]),
+    ("h5", &["p"]),
+    ("h6", &["p"]),
+    ("section", &["p"]),
+    ("header", &["p"]),
+    ("footer", &["p"]),
+    ("blockquote", &["p"]),
+    ("pre", &["p"]),
+];
+
+/// A string of a converted element.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    /// Text as written in the HTML.
+    Literal(String),
+    /// Abbreviation text with `$` markers, numbered by the enclosing
+    /// multiplier.
+    Numbered(String),
+}
+
+/// An element or text node read from the HTML.
+#[derive(Debug, Clone)]
+struct Node {
+    kind: ElementKind,
+    tag: Value,
+    id: Option<Value>,
+    classes: Vec<Value>,
+    attributes: Vec<(String, Option<Value>)>,
+    text: Option<Value>,
+    children: Vec<Node>,
+    multiplier: Option<u32>,
+    self_closing: bool,
+    /// Set on text holding a comment or the doctype, which is kept as
+    /// written.
+    markup: bool,
+}
+
+impl Node {
+    fn element(tag: String) -> Self {
+        Self {
+            kind: ElementKind::Element,
+            tag: Value::Literal(tag),
+            id: None,
+            classes: Vec::new(),
+            attributes: Vec::new(),
+            text: None,
+            children: Vec::new(),
+            multiplier: None,
+            self_closing: false,
+            markup: false,
+        }
+    }
+
+    fn text(text: &str) -> Self {
+        Self {
+            kind: ElementKind::Text,
+            text: Some(Value::Literal(text.to_string())),
+            ..Self::element(String::new())
+        }
+    }
+
+    /// A text node holding a comment or the doctype.
+    fn markup(text: &str) -> Self {
+        Self {
+            markup: true,
+            ..Self::text(text)
+        }
+    }
+
+    /// The text of a text node, unless it is numbered or markup.
+    fn literal_text(&mut self) -> Option<&mut String> {
+        match self {
+            Node {
+                kind: ElementKind::Text,
+                text: Some(Value::Literal(text)),
+                markup: false,
+                ..
+            } => Some(text),
+            _ => None,
+        }
+    }
+
+    fn is_tag(&self, tag: &str) -> bool {
+        self.kind == ElementKind::Element && self.tag == Value::Literal(tag.to_string())
+    }
+
+    /// Sets an attribute read from a start tag, taking `id` and `class` as
+    /// the id and classes when they can be written that way.
+    fn set_attribute(&mut self, name: String, value: Option<String>) {
+        match (name.as_str(), &value) {
+            ("id", Some(id)) if self.id.is_none() && is_id(id) => {
+                self.id = Some(Value::Literal(id.clone()));
+                return;
+            }
+            ("class", Some(classes))
+                if self.classes.is_empty() && classes.split_whitespace().all(is_class) =>
+            {
+                self.classes = classes
+                    .split_whitespace()
+                    .map(|class| Value::Literal(class.to_string()))
+                    .collect();
+                return;
+            }
+            _ => {}
+        }
+
+        let set = self.attributes.iter().any(|(set, _)| *set == name);
+        if !set && is_attribute_name(&name) {
+            self.attributes
+                .push((name, value.map(|value| Value::Literal(decode(&value)))));
+        }
+    }
+
+    /// Converts the node and its children, escaping literal `$` so that it
+    /// is not read as numbering, a field or a variable.
+    fn into_element(self) -> EmmetElement {
+        let value = |value: Value| match value {
+            Value::Literal(text) => text.replace('$', "\\$"),
+            Value::Numbered(text) => text,
+        };
+
+        let mut element = EmmetElement::new(&value(self.tag));
+        element.kind = self.kind;
+        element.id = self.id.map(value);
+        element.classes = self.classes.into_iter().map(value).collect();
+        element.attributes = self
+            .attributes
+            .into_iter()
+            .map(|(name, attribute)| Attribute {
+                boolean: attribute.is_none()
+                    && !BOOLEAN_ATTRIBUTES.contains(&name.to_ascii_lowercase().as_str()),
+                name,
+                value: attribute.map(value),
+                span: Span::default(),
+                spans: AttributeSpans::default(),
+            })
+            .collect();
+        element.text = self.text.map(value);
+        if self.markup {
+            if let Some((kind, text)) = element.text.as_deref().and_then(markup) {
+                element.kind = kind;
+                element.text = Some(text.to_string());
+            }
+        }
+        element.multiplier = self.multiplier;
+        element.self_closing = self.self_closing;
+        element.children = self.children.into_iter().map(Node::into_element).collect();
+        element
+    }
+}
+
+/// Reads HTML into a tree of nodes, closing elements the way browsers do
+/// when their end tags are missing.
+struct HtmlReader<'a> {
+    rest: &'a str,
+    /// Elements whose end tag has not been read yet, innermost last.
+    open: Vec<Node>,
+    nodes: Vec<Node>,
+}
+
+impl<'a> HtmlReader<'a> {
+    fn new(html: &'a str) -> Self {
+        Self {
+            rest: html,
+            open: Vec::new(),
+            nodes: Vec::new(),
+        }
+    }
+
+    fn read(mut self) -> Vec<Node> {
+        while !self.rest.is_empty() {
+            if self.rest.starts_with("<!--") {
+                // Comments are kept as text, like the `c` snippet writes them
+                let end = self.rest[4..]
+                    .find("-->")
+                    .map_or(self.rest.len(), |end| end + 7);
+                let comment = match self.rest[..end].ends_with("-->") {
+                    true => self.rest[..end].to_string(),
+                    false => format!("{}-->", &self.rest[..end]),
+                };
+                self.push(Node::markup(&comment));
+                self.rest = &self.rest[end..];
+            } else if let Some(tag) = self
+                .rest
+                .strip_prefix("</")
+                .filter(|tag| tag_name(tag).is_empty() || is_tag_name(&tag_name(tag)))
+            {
+                self.rest = tag;
+                let name = self.name();
+                self.skip_past('>');
+                self.end(&name);
+            } else if self.rest.starts_with("<!") {
+                // The doctype is kept as text, like the `!!!` snippet writes it
+                let end = self.rest.find('>').map_or(self.rest.len(), |end| end + 1);
+                self.push(Node::markup(&self.rest[..end]));
+                self.rest = self.rest[end..].trim_start();
+            } else if self.rest.starts_with("<?") {
+                self.skip_past('>');
+            } else if self.rest.starts_with('<') && is_tag_name(&tag_name(&self.rest[1..])) {
+                self.rest = &self.rest[1..];
+                self.start_tag();
+            } else {
+                // Text runs to the next tag; a `<` that starts none is text
+                let first = self.rest.chars().next().map_or(0, char::len_utf8);
+                let end = self.rest[first..]
+                    .find('<')
+                    .map_or(self.rest.len(), |end| end + first);
+                self.push_text(&self.rest[..end]);
+                self.rest = &self.rest[end..];
+            }
+        }
+
+        while !self.open.is_empty() {
+            self.close();
+        }
+        self.nodes
+    }
+
+    /// Reads a start tag after its `<`, with its content if it is a raw
+    /// text element.
+    fn start_tag(&mut self) {
+        let tag = self.name();
+        while let Some(top) = self.open.last() {
+            let ends = IMPLIED_END_TAGS
+                .iter()
+                .any(|(start, ends)| *start == tag && ends.iter().any(|end| top.is_tag(end)));
+            if !ends {
+                break;
+            }
+            self.close();
+        }
+
+        let mut node = Node::element(tag.clone());
+        loop {
+            self.rest = self.rest.trim_start();
+            if let Some(rest) = self.rest.strip_prefix("/>") {
+                self.rest = rest;
+                node.self_closing = !crate::elements::builtin_elements().is_void(&tag);
+                self.push(node);
+                return;
+            }
+            if let Some(rest) = self.rest.strip_prefix('>') {
+                self.rest = rest;
+                break;
+            }
+            if self.rest.is_empty() {
+                break;
+            }
+            self.attribute(&mut node);
+        }
+
+        if crate::elements::builtin_elements().is_void(&tag) {
+            self.push(node);
+        } else if RAW_TEXT_ELEMENTS.contains(&tag.as_str()) {
+            let end = self
+                .rest
+                .to_ascii_lowercase()
+                .find(&format!("</{}", tag))
+                .unwrap_or(self.rest.len());
+            if !self.rest[..end].trim().is_empty() {
+                node.children.push(Node::text(&self.rest[..end]));
+            }
+            self.rest = &self.rest[end..];
+            self.open.push(node);
+        } else {
+            self.open.push(node);
+        }
+    }
+
+    /// Reads an attribute of a start tag.
+    fn attribute(&mut self, node: &mut Node) {
+        let end = self
+            .rest
+            .find(|ch: char| ch.is_whitespace() || matches!(ch, '=' | '>' | '/'))
+            .unwrap_or(self.rest.len())
+            // A stray `/` is skipped like a name of its own
+            .max(1);
+        let name = self.rest[..end].to_ascii_lowercase();
+        self.rest = &self.rest[end..];
+
+        let Some(value) = self.rest.trim_start().strip_prefix('=') else {
+            if name != "/" {
+                node.set_attribute(name, None);
+            }
+            return;
+        };
+        let value = value.trim_start();
+        let (value, rest) = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let value = &value[1..];
+                match value.find(quote) {
+                    Some(end) => (&value[..end], &value[end + 1..]),
+                    None => (value, ""),
+                }
+            }
+            _ => {
+                let end = value
+                    .find(|ch: char| ch.is_whitespace() || ch == '>')
+                    .unwrap_or(value.len());
+                (&value[..end], &value[end..])
+            }
+        };
+        node.set_attribute(name, Some(value.to_string()));
+        self.rest = rest;
+    }
+
+    /// Reads a tag name, in lowercase.
+    fn name(&mut self) -> String {
+        let name = tag_name(self.rest);
+        self.rest = &self.rest[name.len()..];
+        name
+    }
+
+    fn skip_past(&mut self, ch: char) {
+        self.rest = self.rest.find(ch).map_or("", |end| &self.rest[end + 1..]);
+    }
+
+    /// Handles an end tag, closing the element it names and those opened
+    /// inside it. End tags of elements that are not open are ignored.
+    fn end(&mut self, tag: &str) {
+        if let Some(index) = self.open.iter().rposition(|node| node.is_tag(tag)) {
+            while self.open.len() > index {
+                self.close();
+            }
+        }
+    }
+
+    /// Closes the innermost open element.
+    fn close(&mut self) {
+        let node = self.open.pop().unwrap();
+        self.push(node);
+    }
+
+    /// Adds a node to the innermost open element.
+    fn push(&mut self, node: Node) {
+        match self.open.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => self.nodes.push(node),
+        }
+    }
+
+    /// Adds text, joining it to text right before it.
+    fn push_text(&mut self, text: &str) {
+        let nodes = match self.open.last_mut() {
+            Some(parent) => &mut parent.children,
+            None => &mut self.nodes,
+        };
+        match nodes.last_mut() {
+            Some(Node {
+                kind: ElementKind::Text,
+                text: Some(Value::Literal(previous)),
+                markup: false,
+                ..
+            }) => previous.push_str(text),
+            _ => nodes.push(Node::text(text)),
+        }
+    }
+}
+
+/// Collapses whitespace in text, unless `preformatted`, and moves the text
+/// of elements whose only child is text into the element.
+fn normalize(nodes: &mut Vec<Node>, preformatted: bool) {
+    if !preformatted {
+        for node in nodes.iter_mut() {
+            if let Some(text) = node.literal_text() {
+                *text = collapse_whitespace(text);
+            }
+        }
+        nodes.retain_mut(|node| {
+            node.literal_text()
+                .is_none_or(|text| !text.trim().is_empty())
+        });
+        if let Some(text) = nodes.first_mut().and_then(Node::literal_text) {
+            *text = text.trim_start().to_string();
+        }
+        if let Some(text) = nodes.last_mut().and_then(Node::literal_text) {
+            *text = text.trim_end().to_string();
+        }
+    }
+
+    for node in nodes.iter_mut() {
+        let preformatted = preformatted || PREFORMATTED_ELEMENTS.iter().any(|tag| node.is_tag(tag));
+        normalize(&mut node.children, preformatted);
+        if let [child] = node.children.as_slice() {
+            if child.kind == ElementKind::Text && node.kind == ElementKind::Element {
+                node.text = node.children.pop().unwrap().text;
+            }
+        }
+    }
+}
+
+/// Replaces each run of whitespace with a single space.
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if !ch.is_whitespace() {
+            collapsed.push(ch);
+        } else if !collapsed.ends_with(' ') {
+            collapsed.push(' ');
+        }
+    }
+    collapsed
+}
+
+/// Replaces runs of identical siblings with one element and a multiplier.
+fn compress(nodes: Vec<Node>) -> Vec<Node> {
+    let nodes: Vec<Node> = nodes
+        .into_iter()
+        .map(|mut node| {
+            node.children = compress(std::mem::take(&mut node.children));
+            node
+        })
+        .collect();
+
+    let mut compressed = Vec::with_capacity(nodes.len());
+    let mut start = 0;
+    while start < nodes.len() {
+        let mut run = Run::new(&nodes[start]);
+        let mut end = start + 1;
+        while end < nodes.len() && run.push(&nodes[end]) {
+            end += 1;
+        }
+        compressed.push(run.finish());
+        start = end;
+    }
+    compressed
+}
+
+/// A run of siblings that are the same apart from numbered strings,
+/// extended one node at a time.
+struct Run<'n> {
+    first: &'n Node,
+    count: u32,
+    /// The strings of the first node, in the order [`strings`] lists them.
+    strings: Vec<Str<'n>>,
+    /// For each string, the number it counts with across the run, or
+    /// `None` while it is the same in every node.
+    counters: Vec<Option<Counter<'n>>>,
+}
+
+/// A string of a node in a run.
+struct Str<'n> {
+    value: &'n Value,
+    /// Whether the string may be numbered. Strings in nested multipliers
+    /// may not, as their `$` count their own repetitions.
+    numbered: bool,
+}
+
+impl<'n> Run<'n> {
+    fn new(first: &'n Node) -> Self {
+        let mut strings = Vec::new();
+        collect_strings(first, true, &mut strings);
+        Self {
+            first,
+            count: 1,
+            counters: vec![None; strings.len()],
+            strings,
+        }
+    }
+
+    /// Adds `node` to the run if it can be rendered by the same element,
+    /// leaving the run as it was otherwise.
+    fn push(&mut self, node: &'n Node) -> bool {
+        if !same_shape(self.first, node) {
+            return false;
+        }
+        let mut strings = Vec::with_capacity(self.strings.len());
+        collect_strings(node, true, &mut strings);
+
+        let mut counters = self.counters.clone();
+        for ((first, string), counter) in self.strings.iter().zip(&strings).zip(&mut counters) {
+            let fits = match counter {
+                Some(counter) => counter.push(string.value),
+                None if string.value == first.value => true,
+                // Only the second node can start a count
+                None if first.numbered && self.count == 1 => {
+                    *counter = Counter::new(first.value, string.value);
+                    counter.is_some()
+                }
+                None => false,
+            };
+            if !fits {
+                return false;
+            }
+        }
+        self.counters = counters;
+        self.count += 1;
+        true
+    }
+
+    /// The element rendering every node of the run.
+    fn finish(self) -> Node {
+        if self.count == 1 {
+            return self.first.clone();
+        }
+        let mut values = self
+            .strings
+            .iter()
+            .zip(&self.counters)
+            .map(|(string, counter)| match counter {
+                Some(counter) => Value::Numbered(counter.marker()),
+                None => string.value.clone(),
+            });
+        let mut merged = replace_strings(self.first, &mut values);
+        merged.multiplier = Some(self.count);
+        merged
+    }
+}
+
+/// Whether two nodes are the same apart from their strings.
+fn same_shape(a: &Node, b: &Node) -> bool {
+    a.kind == b.kind
+        && a.markup == b.markup
+        && a.multiplier == b.multiplier
+        && a.self_closing == b.self_closing
+        && a.id.is_some() == b.id.is_some()
+        && a.classes.len() == b.classes.len()
+        && a.text.is_some() == b.text.is_some()
+        && a.attributes.len() == b.attributes.len()
+        && a.attributes
+            .iter()
+            .zip(&b.attributes)
+            .all(|((a, a_value), (b, b_value))| a == b && a_value.is_some() == b_value.is_some())
+        && a.children.len() == b.children.len()
+        && a.children
+            .iter()
+            .zip(&b.children)
+            .all(|(a, b)| same_shape(a, b))
+}
+
+/// Lists the strings of `node` and its children.
+fn collect_strings<'n>(node: &'n Node, numbered: bool, strings: &mut Vec<Str<'n>>) {
+    let mut push = |value: &'n Value| strings.push(Str { value, numbered });
+    push(&node.tag);
+    node.id.iter().for_each(&mut push);
+    node.text.iter().for_each(&mut push);
+    node.classes.iter().for_each(&mut push);
+    for (_, value) in &node.attributes {
+        value.iter().for_each(&mut push);
+    }
+    for child in &node.children {
+        collect_strings(child, numbered && child.multiplier.is_none(), strings);
+    }
+}
+
+/// Copies `node` with its strings, in the order of [`collect_strings`],
+/// taken from `values`.
+fn replace_strings(node: &Node, values: &mut impl Iterator<Item = Value>) -> Node {
+    let mut next = || values.next().expect("a value for every string");
+    let tag = next();
+    let id = node.id.as_ref().map(|_| next());
+    let text = node.text.as_ref().map(|_| next());
+    let classes = node.classes.iter().map(|_| next()).collect();
+    let attributes = node
+        .attributes
+        .iter()
+        .map(|(name, value)| (name.clone(), value.as_ref().map(|_| next())))
+        .collect();
+    Node {
+        kind: node.kind,
+        tag,
+        id,
+        classes,
+        attributes,
+        text,
+        children: node
+            .children
+            .iter()
+            .map(|child| replace_strings(child, values))
+            .collect(),
+        multiplier: node.multiplier,
+        self_closing: node.self_closing,
+        markup: node.markup,
+    }
+}
+
+/// A number counting up or down by one across the strings of a run, as
+/// in `item1`, `item2`.
+#[derive(Clone, Copy)]
+struct Counter<'n> {
+    /// The text around the number, which does not end or start in digits.
+    before: &'n str,
+    after: &'n str,
+    first: u32,
+    last: u32,
+    step: i64,
+    /// The widths every number so far is zero-padded to, out of `1` (no
+    /// padding) and the width of the first one.
+    widths: [Option<usize>; 2],
+}
+
+impl<'n> Counter<'n> {
+    /// Finds the number counting from `first` to `second`.
+    fn new(first: &'n Value, next: &Value) -> Option<Self> {
+        let (Value::Literal(first), Value::Literal(second)) = (first, next) else {
+            return None;
+        };
+        let is_digit = |ch: char| ch.is_ascii_digit();
+
+        let prefix = first
+            .char_indices()
+            .zip(second.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(first.len().min(second.len()), |((offset, _), _)| offset);
+        let prefix = first[..prefix].trim_end_matches(is_digit).len();
+        let suffix: usize = first[prefix..]
+            .chars()
+            .rev()
+            .zip(second[prefix.min(second.len())..].chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(ch, _)| ch.len_utf8())
+            .sum();
+        let suffix = first[first.len() - suffix..]
+            .trim_start_matches(is_digit)
+            .len();
+
+        let (before, after) = (&first[..prefix], &first[first.len() - suffix..]);
+        // `@` would be read as a modifier of the marker, and `\` would escape it
+        if after.starts_with('@') || before.ends_with('\\') {
+            return None;
+        }
+
+        let digits = first.get(prefix..first.len().checked_sub(suffix)?)?;
+        if digits.is_empty() || !digits.chars().all(is_digit) {
+            return None;
+        }
+        let number = digits.parse().ok()?;
+        let counter = Self {
+            before,
+            after,
+            first: number,
+            last: number,
+            step: 0,
+            widths: [1, digits.len()].map(|width| padded(digits, number, width)),
+        };
+        [1, -1].into_iter().find_map(|step| {
+            let mut counter = Self { step, ..counter };
+            counter.push(next).then_some(counter)
+        })
+    }
+
+    /// Counts `value` if it holds the next number.
+    fn push(&mut self, value: &Value) -> bool {
+        let Value::Literal(text) = value else {
+            return false;
+        };
+        let Some(digits) = text
+            .strip_prefix(self.before)
+            .and_then(|text| text.strip_suffix(self.after))
+        else {
+            return false;
+        };
+        if digits.is_empty() || !digits.chars().all(|ch| ch.is_ascii_digit()) {
+            return false;
+        }
+        let Ok(number) = digits.parse::<u32>() else {
+            return false;
+        };
+        if number as i64 != self.last as i64 + self.step {
+            return false;
+        }
+
+        let widths = self
+            .widths
+            .map(|width| width.and_then(|width| padded(digits, number, width)));
+        if widths == [None, None] {
+            return false;
+        }
+        self.widths = widths;
+        self.last = number;
+        true
+    }
+
+    /// The `$` marker writing the numbers counted.
+    fn marker(&self) -> String {
+        let width = self.widths.into_iter().flatten().next().unwrap_or(1);
+        let modifier = match (self.step, self.first, self.last) {
+            (1, 1, _) => String::new(),
+            (1, start, _) => format!("@{}", start),
+            (_, _, 1) => "@-".to_string(),
+            (_, _, start) => format!("@-{}", start),
+        };
+        let escape = |text: &str| text.replace('$', "\\$");
+        format!(
+            "{}{}{}{}",
+            escape(self.before),
+            "$".repeat(width),
+            modifier,
+            escape(self.after)
+        )
+    }
+}
+
+/// Returns `width` if `digits` writes `number` zero-padded to it.
+fn padded(digits: &str, number: u32, width: usize) -> Option<usize> {
+    (digits == format!("{:0width$}", number, width = width)).then_some(width)
+}
+
+/// Decodes the entities that rendering encodes again in attribute values,
+/// in one pass. `&amp;` before another reference, as in `&amp;lt;`, is
+/// kept, as rendering writes references as they are.
+fn decode(value: &str) -> String {
+    const ENTITIES: [(&str, char); 6] = [
+        ("&quot;", '"'),
+        ("&#34;", '"'),
+        ("&#39;", '\''),
+        ("&apos;", '\''),
+        ("&lt;", '<'),
+        ("&amp;", '&'),
+    ];
+
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = ENTITIES.iter().find(|(entity, _)| {
+            rest.starts_with(entity) && !(*entity == "&amp;" && is_reference(&rest[entity.len()..]))
+        });
+        match entity {
+            Some((entity, ch)) => {
+                decoded.push(*ch);
+                rest = &rest[entity.len()..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// The tag name at the start of `tag`, in lowercase.
+fn tag_name(tag: &str) -> String {
+    let end = tag
+        .find(|ch: char| ch.is_whitespace() || matches!(ch, '/' | '>'))
+        .unwrap_or(tag.len());
+    tag[..end].to_ascii_lowercase()
+}
+
+fn is_tag_name(tag: &str) -> bool {
+    tag.starts_with(|ch: char| ch.is_ascii_alphabetic())
+        && tag
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || matches!(ch, '-' | ':'))
+}
+
+fn is_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || matches!(ch, '-' | '_'))
+}
+
+fn is_class(class: &str) -> bool {
+    !class.ends_with('/')
+        && class
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || matches!(ch, '-' | '_' | '/' | ':'))
+}
+
+fn is_attribute_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || matches!(ch, '-' | '_'))
+}
@@ -0,0 +1,397 @@
+use crate::cursor::Cursor;
+use crate::Span;
+
+/// What a [`Token`] of an abbreviation is.
+///
+/// Names and values are separate tokens from the punctuation that
+/// introduces them: `.item` is a [`Dot`](TokenKind::Dot) followed by a
+/// [`ClassName`](TokenKind::ClassName).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// A tag or snippet name, such as `div` or `a:link`. The `+` ending
+    /// snippet names like `ul+` is lexed as a [`Sibling`](TokenKind::Sibling).
+    TagName,
+    /// `#` before an id.
+    Hash,
+    Id,
+    /// `.` before a class name, or after an attribute name to mark it
+    /// boolean, as in `[hidden.]`.
+    Dot,
+    ClassName,
+    /// `[`
+    AttributesStart,
+    AttributeName,
+    /// `=` between an attribute name and its value.
+    Equals,
+    /// `"` or `'` around an attribute value.
+    Quote,
+    /// An attribute value, without its quotes.
+    AttributeValue,
+    /// `,` between attributes.
+    Comma,
+    /// `]`
+    AttributesEnd,
+    /// `{`
+    TextStart,
+    /// Text content, without its braces. Braces in the text are balanced
+    /// or escaped as `\{` and `\}`.
+    Text,
+    /// `}`
+    TextEnd,
+    /// `*` before a multiplier count.
+    Asterisk,
+    /// A multiplier count.
+    Number,
+    /// `/` marking an element self-closing.
+    Slash,
+    /// `>`
+    Child,
+    /// `+`
+    Sibling,
+    /// `^`, one token per level climbed.
+    ClimbUp,
+    /// `(`
+    GroupStart,
+    /// `)`
+    GroupEnd,
+    /// `|` before a filter name.
+    Pipe,
+    FilterName,
+    Whitespace,
+    /// A character that cannot appear where it was written.
+    Unknown,
+}
+
+/// A token of an abbreviation with where it was written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+/// Splits an abbreviation into tokens, for syntax highlighting and for
+/// [`EmmetParser`](crate::EmmetParser).
+///
+/// The tokens cover the whole input without gaps, and any input can be
+/// lexed: characters that cannot appear in an abbreviation become
+/// [`Unknown`](TokenKind::Unknown) tokens, and unclosed brackets and quotes
+/// run to the end of input.
+///
+/// ```
+/// use emmet_parser::{Lexer, TokenKind};
+///
+/// let input = "ul>li.item{Hi}*2";
+/// let tokens: Vec<_> = Lexer::new(input)
+///     .map(|token| (token.kind, &input[token.span.byte_range()]))
+///     .collect();
+/// assert_eq!(
+///     tokens,
+///     [
+///         (TokenKind::TagName, "ul"),
+///         (TokenKind::Child, ">"),
+///         (TokenKind::TagName, "li"),
+///         (TokenKind::Dot, "."),
+///         (TokenKind::ClassName, "item"),
+///         (TokenKind::TextStart, "{"),
+///         (TokenKind::Text, "Hi"),
+///         (TokenKind::TextEnd, "}"),
+///         (TokenKind::Asterisk, "*"),
+///         (TokenKind::Number, "2"),
+///     ]
+/// );
+/// ```
+pub struct Lexer<'a> {
+    cursor: Cursor<'a>,
+    mode: Mode,
+    /// The name that may follow the last token, such as a class name after
+    /// a `.`.
+    name: Option<TokenKind>,
+}
+
+/// Where in the abbreviation the lexer is, which decides how characters are
+/// read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Abbreviation,
+    Attributes,
+    /// Right after the `=` of an attribute.
+    Value,
+    /// Inside a value quoted with the given character.
+    Quoted(char),
+    Text,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            cursor: Cursor::new(input),
+            mode: Mode::Abbreviation,
+            name: None,
+        }
+    }
+
+    /// Reads the token at the current position, which is not the end of
+    /// input.
+    fn token(&mut self, ch: char) -> TokenKind {
+        match self.mode {
+            Mode::Abbreviation => self.abbreviation_token(ch),
+            Mode::Attributes => self.attribute_token(ch),
+            Mode::Value => {
+                self.mode = Mode::Attributes;
+                if matches!(ch, '"' | '\'') {
+                    self.cursor.bump();
+                    self.mode = Mode::Quoted(ch);
+                    TokenKind::Quote
+                } else if self.eat_unquoted_value() {
+                    TokenKind::AttributeValue
+                } else {
+                    self.attribute_token(ch)
+                }
+            }
+            Mode::Quoted(quote) if ch == quote => {
+                self.cursor.bump();
+                self.mode = Mode::Attributes;
+                TokenKind::Quote
+            }
+            Mode::Quoted(quote) => {
+                // A backslash keeps the quote in the value, as in `"say \"hi\""`,
+                // and `\\` is a backslash
+                while let Some(ch) = self.cursor.peek() {
+                    if ch == quote {
+                        break;
+                    }
+                    self.cursor.bump();
+                    if ch == '\\'
+                        && matches!(self.cursor.peek(), Some(next) if next == quote || next == '\\')
+                    {
+                        self.cursor.bump();
+                    }
+                }
+                TokenKind::AttributeValue
+            }
+            Mode::Text if ch == '}' => {
+                self.cursor.bump();
+                self.mode = Mode::Abbreviation;
+                TokenKind::TextEnd
+            }
+            Mode::Text => {
+                // Braces inside the text, as in `{${1:Document}}`, must be
+                // balanced or escaped with a backslash, and `\\` is a backslash
+                let mut depth = 0;
+                while let Some(ch) = self.cursor.peek() {
+                    match ch {
+                        '\\' if matches!(self.cursor.peek_second(), Some('{' | '}' | '\\')) => {
+                            self.cursor.bump();
+                        }
+                        '{' => depth += 1,
+                        '}' if depth == 0 => break,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    self.cursor.bump();
+                }
+                TokenKind::Text
+            }
+        }
+    }
+
+    fn abbreviation_token(&mut self, ch: char) -> TokenKind {
+        if let Some(kind) = self.name.take() {
+            let eaten = match kind {
+                TokenKind::Id => self.eat_name(is_identifier_char),
+                TokenKind::ClassName => self.eat_name(is_class_char),
+                TokenKind::Number => self.eat(|ch| ch.is_ascii_digit()),
+                _ => self.eat(|ch| ch.is_ascii_alphanumeric()),
+            };
+            if eaten {
+                return kind;
+            }
+        }
+
+        let (kind, name) = match ch {
+            _ if ch.is_whitespace() => {
+                self.eat(char::is_whitespace);
+                return TokenKind::Whitespace;
+            }
+            _ if ch == '$' || is_tag_char(ch) => {
+                self.eat_name(is_tag_char);
+                return TokenKind::TagName;
+            }
+            '#' => (TokenKind::Hash, Some(TokenKind::Id)),
+            '.' => (TokenKind::Dot, Some(TokenKind::ClassName)),
+            '*' => (TokenKind::Asterisk, Some(TokenKind::Number)),
+            '|' => (TokenKind::Pipe, Some(TokenKind::FilterName)),
+            '[' => {
+                self.mode = Mode::Attributes;
+                (TokenKind::AttributesStart, None)
+            }
+            '{' => {
+                self.mode = Mode::Text;
+                (TokenKind::TextStart, None)
+            }
+            '/' => (TokenKind::Slash, None),
+            '>' => (TokenKind::Child, None),
+            '+' => (TokenKind::Sibling, None),
+            '^' => (TokenKind::ClimbUp, None),
+            '(' => (TokenKind::GroupStart, None),
+            ')' => (TokenKind::GroupEnd, None),
+            _ => (TokenKind::Unknown, None),
+        };
+        self.cursor.bump();
+        self.name = name;
+        kind
+    }
+
+    fn attribute_token(&mut self, ch: char) -> TokenKind {
+        let kind = match ch {
+            _ if ch.is_whitespace() => {
+                self.eat(char::is_whitespace);
+                return TokenKind::Whitespace;
+            }
+            _ if ch == '$' || is_identifier_char(ch) => {
+                self.eat_name(is_identifier_char);
+                return TokenKind::AttributeName;
+            }
+            ']' => {
+                self.mode = Mode::Abbreviation;
+                TokenKind::AttributesEnd
+            }
+            '=' => {
+                self.mode = Mode::Value;
+                TokenKind::Equals
+            }
+            '.' => TokenKind::Dot,
+            ',' => TokenKind::Comma,
+            _ => TokenKind::Unknown,
+        };
+        self.cursor.bump();
+        kind
+    }
+
+    /// Consumes characters while `predicate` holds. Returns whether any
+    /// were consumed.
+    fn eat(&mut self, predicate: impl FnMut(char) -> bool) -> bool {
+        let start = self.cursor.offset();
+        self.cursor.eat_while(predicate);
+        self.cursor.offset() > start
+    }
+
+    /// Consumes a name made of characters matching `predicate` and `$`
+    /// numbering markers.
+    fn eat_name(&mut self, is_name_char: fn(char) -> bool) -> bool {
+        let start = self.cursor.offset();
+        while let Some(ch) = self.cursor.peek() {
+            if self.eat_numbering() {
+                continue;
+            }
+            // A `/` ends a class name unless more of the name follows, as
+            // in `w-1/2`, so that `.item/` is self-closing
+            let slash_ends = ch == '/' && !self.cursor.peek_second().is_some_and(is_name_char);
+            if !is_name_char(ch) || slash_ends {
+                break;
+            }
+            self.cursor.bump();
+        }
+        self.cursor.offset() > start
+    }
+
+    /// Consumes an attribute value written without quotes. Values may hold
+    /// spaces, except before another `name=value` attribute.
+    fn eat_unquoted_value(&mut self) -> bool {
+        let start = self.cursor.offset();
+        while let Some(ch) = self.cursor.peek() {
+            if self.eat_field() || self.eat_numbering() {
+                continue;
+            }
+            if ch == ' ' && self.at_next_attribute() {
+                break;
+            }
+            if ch.is_alphanumeric() || matches!(ch, '-' | '_' | ' ' | '.') {
+                self.cursor.bump();
+            } else {
+                break;
+            }
+        }
+        self.cursor.offset() > start
+    }
+
+    /// Checks whether the whitespace at the current position separates an
+    /// unquoted value from a following `name=value` attribute.
+    fn at_next_attribute(&self) -> bool {
+        let rest = self.cursor.rest().trim_start();
+        let name_len = rest
+            .find(|ch: char| !is_identifier_char(ch) && !matches!(ch, '$' | '@'))
+            .unwrap_or(rest.len());
+        name_len > 0 && rest[name_len..].starts_with('=')
+    }
+
+    /// Consumes a `${...}` field or variable reference.
+    fn eat_field(&mut self) -> bool {
+        if self.cursor.peek() != Some('$') || self.cursor.peek_second() != Some('{') {
+            return false;
+        }
+
+        self.cursor.bump();
+        self.cursor.bump();
+        let mut depth = 0;
+        while let Some(ch) = self.cursor.bump() {
+            match ch {
+                '{' => depth += 1,
+                '}' if depth == 0 => break,
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+
+        true
+    }
+
+    /// Consumes a `$` numbering marker together with its `@` modifier.
+    fn eat_numbering(&mut self) -> bool {
+        if !self.eat(|ch| ch == '$') {
+            return false;
+        }
+
+        if self.cursor.peek() == Some('@') {
+            self.cursor.bump();
+            if self.cursor.peek() == Some('-') {
+                self.cursor.bump();
+            }
+            self.eat(|ch| ch.is_ascii_digit());
+        }
+
+        true
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        let start = self.cursor.offset();
+        let ch = self.cursor.peek()?;
+        let kind = self.token(ch);
+        Some(Token {
+            kind,
+            span: self.cursor.span_from(start),
+        })
+    }
+}
+
+/// Splits `input` into tokens. See [`Lexer`].
+pub fn tokenize(input: &str) -> Vec<Token> {
+    Lexer::new(input).collect()
+}
+
+fn is_tag_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '-' | ':' | '!')
+}
+
+/// Characters of ids and attribute names.
+fn is_identifier_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '-' | '_')
+}
+
+fn is_class_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '-' | '_' | '/' | ':')
+}
@@ -1,35 +1,212 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
+
 use thiserror::Error;
 
+mod borrowed;
+mod config;
+mod cursor;
+mod elements;
+mod error;
+mod escape;
+mod expand;
+mod fields;
+mod format;
+mod from_html;
+mod lexer;
+mod numbering;
+mod printer;
+mod profile;
+mod render;
+mod snippets;
+mod span;
+mod user_snippets;
+#[cfg(feature = "serde")]
+mod versioned;
+mod visit;
+
+pub use borrowed::{BorrowedAttribute, BorrowedElement};
+pub use config::ExpandConfig;
+pub use elements::{implicit_tag, ElementInfo, ElementTable};
+pub use error::{ErrorCode, Expected, ParseError};
+pub use expand::{expand, expand_with_elements, ExpandedElement, ExpandedNode};
+pub use fields::{FieldFormatter, LspFields, PlainFields, TextMateFields};
+pub use format::FormatOptions;
+pub use from_html::{html_to_abbreviation, html_to_elements};
+pub use lexer::{tokenize, Lexer, Token, TokenKind};
+pub use printer::to_abbreviation;
+pub use profile::{Case, Profile, Quote, SelfClosingStyle};
+pub use render::Renderer;
+pub use snippets::SnippetRegistry;
+pub use span::{AttributeSpans, ElementSpans, Span};
+pub use user_snippets::UserSnippets;
+#[cfg(feature = "serde")]
+pub use versioned::{Versioned, FORMAT_VERSION};
+pub use visit::{Fold, Visitor, VisitorMut};
+
+use cursor::Offset;
+use fields::renumber_fields;
+
 #[derive(Error, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "kind", content = "detail", rename_all = "snake_case")
+)]
 pub enum EmmetError {
     #[error("Invalid syntax: {0}")]
-    InvalidSyntax(String),
-    #[error("Unclosed bracket")]
-    UnclosedBracket,
-    #[error("Invalid attribute syntax")]
-    InvalidAttribute,
+    InvalidSyntax(ParseError),
+    /// A `(`, `[`, `{` or quote without its closing counterpart.
+    #[error("Unclosed bracket: {0}")]
+    UnclosedBracket(ParseError),
+    #[error("Invalid attribute syntax: {0}")]
+    InvalidAttribute(ParseError),
+    /// A `${name}` variable that the configuration does not define, reported
+    /// in strict mode only.
+    #[error("Unknown variable '{0}'")]
+    UnknownVariable(String),
+    /// A user snippet file could not be read or contains invalid snippets.
+    /// `line` and `column` are 1-based, or 0 when the file was not read.
+    #[error("{}:{line}:{column}: {message}", display_file(.file))]
+    SnippetLoad {
+        file: Option<PathBuf>,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl EmmetError {
+    /// Returns the location and details of a parse error.
+    pub fn parse_error(&self) -> Option<&ParseError> {
+        match self {
+            EmmetError::InvalidSyntax(err)
+            | EmmetError::UnclosedBracket(err)
+            | EmmetError::InvalidAttribute(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// Renders the error for display in a terminal, with parse errors
+    /// underlining the offending part of `input` (see [`ParseError::render`]).
+    pub fn render(&self, input: &str) -> String {
+        match self.parse_error() {
+            Some(err) => err.render(input),
+            None => format!("error: {}", self),
+        }
+    }
 }
 
+fn display_file(file: &Option<PathBuf>) -> String {
+    match file {
+        Some(file) => file.display().to_string(),
+        None => "<snippets>".to_string(),
+    }
+}
+
+/// How deeply children and groups may nest, so that parsing and walking a
+/// tree stay within the stack.
+const MAX_NESTING: usize = 128;
+
+/// Attributes rendered without a value when none is given.
+const BOOLEAN_ATTRIBUTES: &[&str] = &[
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "contenteditable",
+    "controls",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "hidden",
+    "ismap",
+    "loop",
+    "multiple",
+    "muted",
+    "novalidate",
+    "readonly",
+    "required",
+    "reversed",
+    "selected",
+    "typemustmatch",
+];
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Attribute {
     pub name: String,
     pub value: Option<String>,
+    /// Set by a trailing `.`, as in `[disabled.]`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub boolean: bool,
+    /// The whole `name=value` pair in the abbreviation.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub spans: AttributeSpans,
+}
+
+impl Attribute {
+    /// Whether the attribute is written without a value. Attributes without
+    /// a value that are not boolean render with an empty one (`href=""`).
+    pub fn is_boolean(&self) -> bool {
+        self.value.is_none()
+            && (self.boolean
+                || BOOLEAN_ATTRIBUTES.contains(&self.name.to_ascii_lowercase().as_str()))
+    }
+}
+
+/// Distinguishes real elements from parenthesized groups, text nodes and
+/// the comments and doctypes of snippets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ElementKind {
+    #[default]
+    Element,
+    /// A `(...)` fragment: only `children` and `multiplier` are meaningful.
+    Group,
+    /// A bare `{...}` without tag, id, classes or attributes, rendered as
+    /// plain text even when it reads like markup.
+    Text,
+    /// A comment written by a snippet, such as `c`, holding what goes
+    /// between `<!--` and `-->` as its `text`.
+    Comment,
+    /// A document type declaration written by a snippet, such as `!!!`,
+    /// holding what follows `<!DOCTYPE` as its `text`, as in `html`.
+    Doctype,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EmmetElement {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kind: ElementKind,
     pub tag: String,
     pub id: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub classes: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub attributes: Vec<Attribute>,
     pub text: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub children: Vec<EmmetElement>,
     pub multiplier: Option<u32>,
+    /// Set by a trailing `/`, as in `br/`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub self_closing: bool,
+    /// The element and its children in the abbreviation.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub spans: ElementSpans,
 }
 
 impl EmmetElement {
     pub fn new(tag: &str) -> Self {
         Self {
+            kind: ElementKind::Element,
             tag: tag.to_string(),
             id: None,
             classes: Vec::new(),
@@ -37,350 +214,1110 @@ impl EmmetElement {
             text: None,
             children: Vec::new(),
             multiplier: None,
+            self_closing: false,
+            span: Span::default(),
+            spans: ElementSpans::default(),
         }
     }
 
+    pub fn group(children: Vec<EmmetElement>) -> Self {
+        Self {
+            kind: ElementKind::Group,
+            children,
+            ..Self::new("")
+        }
+    }
+
+    pub fn is_group(&self) -> bool {
+        self.kind == ElementKind::Group
+    }
+
+    pub fn is_text(&self) -> bool {
+        self.kind == ElementKind::Text
+    }
+
+    /// Renders the element as HTML, with editor fields replaced by their
+    /// placeholders.
     pub fn to_html(&self) -> String {
-        let mut html = String::new();
+        Renderer::new().render_element(self)
+    }
 
-        // Handle multiplication
-        let count = self.multiplier.unwrap_or(1);
-        for i in 0..count {
-            html.push_str(&self.element_to_html(i));
+    /// Renders the element as an editor snippet. Snippet fields, empty
+    /// attribute values and empty element bodies become fields written by
+    /// `formatter`.
+    pub fn to_snippet(&self, formatter: &dyn FieldFormatter) -> String {
+        Renderer::new().with_fields(formatter).render_element(self)
+    }
+}
+
+/// Result of [`EmmetParser::parse_partial`], or with borrowed elements of
+/// [`EmmetParser::parse_partial_borrowed`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartialParse<E = EmmetElement> {
+    /// Everything that could be parsed.
+    pub elements: Vec<E>,
+    /// Errors met on the way, in input order.
+    pub diagnostics: Vec<ParseError>,
+    /// What could be written at the end of the input to continue it, such
+    /// as a class name after `li.` or `]` inside `a[href=x`.
+    pub expected: Vec<Expected>,
+}
+
+pub struct EmmetParser<'s> {
+    input: &'s str,
+    tokens: Vec<Token>,
+    /// Index of the next token to read.
+    index: usize,
+    /// Number of `^` levels still to climb before parsing the next sibling.
+    climb: usize,
+    /// Number of levels of children and groups being parsed, up to
+    /// [`MAX_NESTING`].
+    depth: usize,
+    /// Tag of the element the abbreviation is expanded inside of.
+    parent: Option<String>,
+    /// Snippets resolved while parsing, if any.
+    snippets: Option<&'s SnippetRegistry>,
+    /// Variables substituted into text and attribute values.
+    config: &'s ExpandConfig,
+    /// Element metadata used to resolve implicit tag names.
+    elements: &'s ElementTable,
+    /// Names of the snippets being expanded, to stop self-referencing aliases.
+    snippet_stack: Vec<String>,
+    /// Filters named after the abbreviation, as in `ul>li|e`.
+    filters: Vec<String>,
+    /// The operator consumed before the next element.
+    operator: Option<Span>,
+    /// Whether errors are recorded as diagnostics instead of returned.
+    recover: bool,
+    diagnostics: Vec<ParseError>,
+    /// What may be written at the end of the input to continue it.
+    expected: Vec<Expected>,
+    /// Set by an error at the end of input, after which only what that
+    /// error expects may follow.
+    expected_final: bool,
+}
+
+impl<'s> EmmetParser<'s> {
+    /// Creates a parser that resolves the built-in HTML snippets.
+    pub fn new(input: &'s str) -> Self {
+        Self {
+            input,
+            tokens: lexer::tokenize(input),
+            index: 0,
+            climb: 0,
+            depth: 0,
+            parent: None,
+            snippets: Some(snippets::builtin_html()),
+            config: config::default_config(),
+            elements: elements::builtin_elements(),
+            snippet_stack: Vec::new(),
+            filters: Vec::new(),
+            operator: None,
+            recover: false,
+            diagnostics: Vec::new(),
+            expected: Vec::new(),
+            expected_final: false,
         }
+    }
 
-        html
+    /// Resolves snippets from `snippets` instead of the built-in set.
+    pub fn with_snippets(mut self, snippets: &'s SnippetRegistry) -> Self {
+        self.snippets = Some(snippets);
+        self
     }
 
-    fn element_to_html(&self, _index: u32) -> String {
-        let mut html = String::new();
+    /// Expands with the variables and options of `config`.
+    pub fn with_config(mut self, config: &'s ExpandConfig) -> Self {
+        self.config = config;
+        self
+    }
 
-        // Opening tag
-        html.push('<');
-        html.push_str(&self.tag);
+    /// Resolves implicit tag names from `elements` instead of the HTML
+    /// element table.
+    pub fn with_elements(mut self, elements: &'s ElementTable) -> Self {
+        self.elements = elements;
+        self
+    }
 
-        // ID
-        if let Some(id) = &self.id {
-            html.push_str(&format!(" id=\"{}\"", id));
-        }
+    /// Treats every tag name literally.
+    pub fn without_snippets(mut self) -> Self {
+        self.snippets = None;
+        self
+    }
 
-        // Classes
-        if !self.classes.is_empty() {
-            let classes = self.classes.join(" ");
-            html.push_str(&format!(" class=\"{}\"", classes));
-        }
+    /// Sets the tag of the element enclosing the insertion point, used to
+    /// resolve implicit tag names of top-level elements (`.item` inside a
+    /// `<ul>` becomes `li`).
+    pub fn with_parent(mut self, tag: &str) -> Self {
+        self.parent = Some(tag.to_string());
+        self
+    }
 
-        // Attributes
-        for attr in &self.attributes {
-            if let Some(value) = &attr.value {
-                html.push_str(&format!(" {}=\"{}\"", attr.name, value));
-            } else {
-                html.push_str(&format!(" {}", attr.name));
-            }
-        }
+    pub fn parse(&mut self) -> Result<Vec<EmmetElement>, EmmetError> {
+        let elements = self.parse_borrowed()?;
+        Ok(elements
+            .into_iter()
+            .map(BorrowedElement::into_owned)
+            .collect())
+    }
 
-        // Self-closing tags
-        let self_closing_tags = ["img", "input", "br", "hr", "meta", "link"];
-        if self_closing_tags.contains(&self.tag.as_str()) {
-            html.push_str(" />");
-            return html;
+    /// Parses the abbreviation into elements borrowing their strings from
+    /// the input and snippets, to avoid copying them.
+    pub fn parse_borrowed(&mut self) -> Result<Vec<BorrowedElement<'s>>, EmmetError> {
+        let parent = self.parent.clone();
+        let mut elements = self.parse_siblings(true, parent.as_deref())?;
+
+        while self.peek() == Some(TokenKind::GroupEnd) {
+            let err = self.error_here(ErrorCode::UnmatchedParen, &[]);
+            self.fail(EmmetError::InvalidSyntax(err))?;
+            // Recovering: drop the `)` with the operator after it and go on
+            // with what follows
+            self.bump();
+            self.skip_whitespace();
+            if matches!(self.peek(), Some(TokenKind::Sibling | TokenKind::ClimbUp)) {
+                self.bump();
+            }
+            elements.extend(self.parse_siblings(true, parent.as_deref())?);
         }
+        self.parse_filters()?;
 
-        html.push('>');
+        Ok(elements)
+    }
 
-        // Text content
-        if let Some(text) = &self.text {
-            html.push_str(text);
+    /// Parses the abbreviation in recovery mode, for input that is still
+    /// being written: errors are collected instead of returned, and parsing
+    /// goes on with as much of the tree as can be made sense of.
+    ///
+    /// ```
+    /// use emmet_parser::{EmmetParser, Expected};
+    ///
+    /// let result = EmmetParser::new("div>ul>li.").parse_partial();
+    /// assert_eq!(result.elements[0].children[0].children[0].tag, "li");
+    /// assert_eq!(result.diagnostics[0].message, "expected class name, found end of input");
+    /// assert_eq!(result.expected, [Expected::ClassName]);
+    /// ```
+    pub fn parse_partial(&mut self) -> PartialParse {
+        let result = self.parse_partial_borrowed();
+        PartialParse {
+            elements: result
+                .elements
+                .into_iter()
+                .map(BorrowedElement::into_owned)
+                .collect(),
+            diagnostics: result.diagnostics,
+            expected: result.expected,
         }
+    }
 
-        // Children
-        for child in &self.children {
-            html.push_str(&child.to_html());
-        }
+    /// Parses the abbreviation in recovery mode like
+    /// [`parse_partial`](Self::parse_partial), into borrowed elements.
+    pub fn parse_partial_borrowed(&mut self) -> PartialParse<BorrowedElement<'s>> {
+        self.recover = true;
+        let elements = match self.parse_borrowed() {
+            Ok(elements) => elements,
+            Err(err) => {
+                self.diagnostics.extend(err.parse_error().cloned());
+                Vec::new()
+            }
+        };
 
-        // Closing tag
-        html.push_str(&format!("</{}>", self.tag));
+        PartialParse {
+            elements,
+            diagnostics: std::mem::take(&mut self.diagnostics),
+            expected: std::mem::take(&mut self.expected),
+        }
+    }
 
-        html
+    /// Filters named after the parsed abbreviation, like `e` in `p{a < b}|e`.
+    pub fn filters(&self) -> &[String] {
+        &self.filters
     }
-}
 
-pub struct EmmetParser {
-    input: String,
-    position: usize,
-}
+    /// Parses the `|name` filters ending the abbreviation.
+    fn parse_filters(&mut self) -> Result<(), EmmetError> {
+        while self.eat(TokenKind::Pipe).is_some() {
+            match self.eat(TokenKind::FilterName) {
+                Some(name) => {
+                    self.expect(&[Expected::FilterName]);
+                    self.filters.push(self.text(name).to_string());
+                }
+                None => {
+                    let err = self.error_here(ErrorCode::ExpectedFilter, &[Expected::FilterName]);
+                    self.fail(EmmetError::InvalidSyntax(err))?;
+                }
+            }
+            self.skip_whitespace();
+        }
 
-impl EmmetParser {
-    pub fn new(input: &str) -> Self {
-        Self {
-            input: input.to_string(),
-            position: 0,
+        match self.peek() {
+            Some(_) => {
+                let err = self.error_here(ErrorCode::UnexpectedCharacter, &[Expected::Char('|')]);
+                self.fail(EmmetError::InvalidSyntax(err))?;
+                // Recovering: ignore the rest of the input
+                self.index = self.tokens.len();
+            }
+            None => self.expect(&[Expected::Char('|')]),
         }
+        Ok(())
     }
 
-    pub fn parse(&mut self) -> Result<Vec<EmmetElement>, EmmetError> {
+    /// Parses elements joined by `+` at one nesting level inside `parent`.
+    ///
+    /// Stops at the end of input, at the `)` closing the enclosing group, or
+    /// when a `^` climbs out of this level. The top level of the input and of
+    /// each group cannot be climbed out of, so there extra `^` are ignored.
+    fn parse_siblings(
+        &mut self,
+        top_level: bool,
+        parent: Option<&str>,
+    ) -> Result<Vec<BorrowedElement<'s>>, EmmetError> {
+        if self.depth == MAX_NESTING {
+            return self.nesting_too_deep();
+        }
+        self.depth += 1;
+
         let mut elements = Vec::new();
 
-        while self.position < self.input.len() {
+        loop {
             self.skip_whitespace();
 
-            if self.peek().is_none() {
+            if matches!(
+                self.peek(),
+                None | Some(TokenKind::GroupEnd | TokenKind::Pipe)
+            ) {
+                self.expect(&[Expected::Element]);
                 break;
             }
 
-            let element = self.parse_element()?;
-            elements.push(element);
+            // When recovering from input that does not start an element, the
+            // operators around it are kept
+            elements.extend(self.parse_element(parent)?);
 
-            // Handle siblings
-            if self.peek() == Some('+') {
-                self.consume_char();
-                continue;
+            if self.climb > 0 {
+                // A `^` in the element's children climbed back to this level,
+                // so the next sibling follows without another operator
+                self.climb -= 1;
+            } else {
+                self.skip_whitespace();
+
+                let operator = self.offset();
+                match self.peek() {
+                    Some(TokenKind::Sibling) => {
+                        self.bump();
+                        self.operator = Some(self.span_from(operator));
+                    }
+                    Some(TokenKind::ClimbUp) => {
+                        while self.eat(TokenKind::ClimbUp).is_some() {
+                            self.climb += 1;
+                        }
+                        self.operator = Some(self.span_from(operator));
+                    }
+                    None | Some(TokenKind::GroupEnd | TokenKind::Pipe) => {
+                        // After a dangling operator in the element's
+                        // children, as in `div>`, only an element may follow
+                        if !self.expected.contains(&Expected::Element) {
+                            self.expect(&[Expected::Char('+'), Expected::Char('^')]);
+                        }
+                        break;
+                    }
+                    Some(_) => {
+                        let err = self.error_here(
+                            ErrorCode::UnexpectedCharacter,
+                            &[
+                                Expected::Char('+'),
+                                Expected::Char('>'),
+                                Expected::Char('^'),
+                            ],
+                        );
+                        // Recovering: read what follows as a sibling
+                        self.fail(EmmetError::InvalidSyntax(err))?;
+                    }
+                }
             }
 
-            self.skip_whitespace();
+            if self.climb > 0 {
+                if !top_level {
+                    break;
+                }
+                self.climb = 0;
+            }
         }
 
+        self.depth -= 1;
         Ok(elements)
     }
 
-    fn parse_element(&mut self) -> Result<EmmetElement, EmmetError> {
-        let mut element = EmmetElement::new("div");
-
-        // Parse tag name
-        if let Some(tag) = self.parse_tag_name() {
-            element.tag = tag;
+    /// Reports children or groups nested deeper than [`MAX_NESTING`].
+    fn nesting_too_deep(&mut self) -> Result<Vec<BorrowedElement<'s>>, EmmetError> {
+        let err = self
+            .error_here(ErrorCode::NestingTooDeep, &[])
+            .with_message(format!("nesting deeper than {} levels", MAX_NESTING));
+        self.fail(EmmetError::InvalidSyntax(err))?;
+
+        // Recovering: what is nested deeper is left out, up to the `)`
+        // closing the enclosing group
+        let mut groups = 0;
+        while let Some(kind) = self.peek() {
+            match kind {
+                TokenKind::GroupStart => groups += 1,
+                TokenKind::GroupEnd | TokenKind::Pipe if groups == 0 => break,
+                TokenKind::GroupEnd => groups -= 1,
+                _ => {}
+            }
+            self.bump();
         }
+        Ok(Vec::new())
+    }
+
+    fn parse_group(&mut self, parent: Option<&str>) -> Result<BorrowedElement<'s>, EmmetError> {
+        let start = self.offset();
+        self.bump();
+        let mut group = BorrowedElement::group(self.parse_siblings(true, parent)?);
 
-        // Parse ID
-        if self.peek() == Some('#') {
-            self.consume_char();
-            element.id = Some(self.parse_identifier()?);
+        match self.peek() {
+            Some(TokenKind::GroupEnd) => {
+                self.bump();
+                self.expect(&[Expected::Char('*')]);
+            }
+            Some(_) => {
+                let err = self.error_here(ErrorCode::UnexpectedCharacter, &[Expected::Char(')')]);
+                // Recovering: the group ends here
+                self.fail(EmmetError::InvalidSyntax(err))?;
+            }
+            None => {
+                let err = self.unclosed(ErrorCode::UnclosedGroup, start, Expected::Char(')'));
+                self.fail(EmmetError::UnclosedBracket(err))?;
+            }
         }
 
-        // Parse classes
-        while self.peek() == Some('.') {
-            self.consume_char();
-            let class = self.parse_identifier()?;
-            element.classes.push(class);
+        if self.peek() == Some(TokenKind::Asterisk) {
+            let multiplier = self.offset();
+            self.bump();
+            if let Some(count) = self.parse_number()? {
+                group.multiplier = Some(count);
+                group.spans.multiplier = Some(self.span_from(multiplier));
+            }
+        }
+        group.span = self.span_from(start);
+
+        if self.peek() == Some(TokenKind::Child) {
+            let err = self
+                .error_here(ErrorCode::GroupChildren, &[])
+                .with_message("groups cannot have children".to_string());
+            self.fail(EmmetError::InvalidSyntax(err))?;
+
+            // Recovering: the children go to the group's last element
+            let operator = self.offset();
+            self.bump();
+            self.operator = Some(self.span_from(operator));
+            let parent_tag = group.deepest_last_mut().tag.clone();
+            let children = self.parse_siblings(false, Some(&parent_tag))?;
+            group.deepest_last_mut().children.extend(children);
+            group.span = self.span_from(start);
         }
 
-        // Parse attributes
-        while self.peek() == Some('[') {
-            let attributes = self.parse_attributes()?;
-            element.attributes.extend(attributes);
+        Ok(group)
+    }
+
+    /// Parses an element or group with its children. Returns `None` when
+    /// recovering from input that does not start an element.
+    fn parse_element(
+        &mut self,
+        parent: Option<&str>,
+    ) -> Result<Option<BorrowedElement<'s>>, EmmetError> {
+        let operator = self.operator.take();
+
+        if self.peek() == Some(TokenKind::GroupStart) {
+            let mut group = self.parse_group(parent)?;
+            group.spans.operator = operator;
+            return Ok(Some(group));
         }
 
-        // Parse text content
-        if self.peek() == Some('{') {
-            element.text = Some(self.parse_text_content()?);
+        let start = self.offset();
+        let Some(mut element) = self.parse_element_parts(parent)? else {
+            return Ok(None);
+        };
+
+        // Parse children, inserted at the deepest node of an expanded snippet
+        if self.peek() == Some(TokenKind::Child) {
+            let operator = self.offset();
+            self.bump();
+            self.operator = Some(self.span_from(operator));
+            let parent_tag = element.deepest_last_mut().tag.clone();
+            let children = self.parse_siblings(false, Some(&parent_tag))?;
+            element.deepest_last_mut().children.extend(children);
         }
 
-        // Parse multiplication
-        if self.peek() == Some('*') {
-            self.consume_char();
-            element.multiplier = Some(self.parse_number()?);
+        element.span = self.span_from(start);
+        element.spans.operator = operator;
+        Ok(Some(element))
+    }
+
+    /// Parses an element without its children, expanding it when it names
+    /// a snippet. Kept apart from [`parse_element`](Self::parse_element) so
+    /// that its locals are not on the stack while the children are parsed.
+    fn parse_element_parts(
+        &mut self,
+        parent: Option<&str>,
+    ) -> Result<Option<BorrowedElement<'s>>, EmmetError> {
+        let start = self.offset();
+        let tag = self.parse_tag_name()?;
+        let mut element = BorrowedElement::new(match &tag {
+            Some(tag) => tag.clone(),
+            None => Cow::Borrowed(self.elements.implicit_tag(parent)),
+        });
+        if tag.is_some() {
+            element.spans.tag = Some(self.span_from(start));
+            self.expect(&[Expected::TagName]);
         }
 
-        // Parse children
-        if self.peek() == Some('>') {
-            self.consume_char();
-            element.children = self.parse_children()?;
+        // Parse ID, classes, attributes, text and multiplier in any order
+        loop {
+            let part = self.offset();
+            match self.peek() {
+                Some(TokenKind::Hash) => {
+                    self.bump();
+                    let id =
+                        self.parse_name(TokenKind::Id, ErrorCode::ExpectedName, &[Expected::Id])?;
+                    if let Some(id) = id {
+                        element.id = Some(id);
+                        element.spans.id = Some(self.span_from(part));
+                    }
+                }
+                Some(TokenKind::Dot) => {
+                    self.bump();
+                    let class = self.parse_name(
+                        TokenKind::ClassName,
+                        ErrorCode::ExpectedClassName,
+                        &[Expected::ClassName],
+                    )?;
+                    if let Some(class) = class {
+                        element.classes.push(class);
+                        element.spans.classes.push(self.span_from(part));
+                    }
+                }
+                Some(TokenKind::AttributesStart) => {
+                    let attributes = self.parse_attributes()?;
+                    element.attributes.extend(attributes);
+                }
+                Some(TokenKind::TextStart) => {
+                    let text = self.parse_text_content()?;
+                    let span = self.span_from(part);
+                    let text = self.substitute(text, span)?;
+                    match &mut element.text {
+                        Some(existing) => existing.to_mut().push_str(&text),
+                        None => element.text = Some(text),
+                    }
+                    element.spans.text = Some(match element.spans.text {
+                        Some(text) => text.to(span),
+                        None => span,
+                    });
+                }
+                Some(TokenKind::Asterisk) => {
+                    let repeated = element.multiplier.is_some().then(|| {
+                        self.error_here(ErrorCode::RepeatedMultiplier, &[])
+                            .with_message("element already has a multiplier".to_string())
+                    });
+                    self.bump();
+                    let count = self.parse_number()?;
+                    if let Some(err) = repeated {
+                        // Recovering: the first multiplier is kept
+                        self.fail(EmmetError::InvalidSyntax(err))?;
+                    } else if let Some(count) = count {
+                        element.multiplier = Some(count);
+                        element.spans.multiplier = Some(self.span_from(part));
+                    }
+                }
+                Some(TokenKind::Slash) => {
+                    self.bump();
+                    element.self_closing = true;
+                }
+                Some(_) => break,
+                None => {
+                    self.expect(&[
+                        Expected::Char('#'),
+                        Expected::Char('.'),
+                        Expected::Char('['),
+                        Expected::Char('{'),
+                        Expected::Char('*'),
+                        Expected::Char('/'),
+                        Expected::Char('>'),
+                    ]);
+                    break;
+                }
+            }
         }
 
-        Ok(element)
-    }
+        if self.offset() == start {
+            let err = self.error_here(ErrorCode::ExpectedElement, &[Expected::Element]);
+            self.fail(EmmetError::InvalidSyntax(err))?;
+            // Recovering: skip the token
+            self.bump();
+            return Ok(None);
+        }
 
-    fn parse_tag_name(&mut self) -> Option<String> {
-        let start = self.position;
+        if tag.is_none()
+            && element.id.is_none()
+            && element.classes.is_empty()
+            && element.attributes.is_empty()
+            && element.text.is_some()
+        {
+            element.kind = ElementKind::Text;
+            element.tag = Cow::Borrowed("");
+
+            // Markup is only read from snippets, so that text written in
+            // the abbreviation is never rendered unescaped
+            if !self.snippet_stack.is_empty() {
+                if let Some((kind, text)) = element.text.as_deref().and_then(markup) {
+                    element.kind = kind;
+                    element.text = Some(Cow::Owned(text.to_string()));
+                }
+            }
+        }
 
-        while self.position < self.input.len() {
-            let ch = self.input.chars().nth(self.position)?;
-            if ch.is_alphanumeric() || ch == '-' {
-                self.position += 1;
-            } else {
-                break;
+        if let Some(name) = tag {
+            if let Some(abbreviation) = self.snippet(&name) {
+                element = match self.expand_snippet(&name, abbreviation, element.clone(), parent) {
+                    Ok(expanded) => expanded,
+                    // Recovering: keep the element as written
+                    Err(err) => {
+                        self.fail(err)?;
+                        element
+                    }
+                };
             }
         }
 
-        if self.position > start {
-            Some(self.input[start..self.position].to_string())
-        } else {
-            None
+        Ok(Some(element))
+    }
+
+    /// Looks up a snippet, ignoring those already being expanded so that
+    /// aliases such as `input` → `input[type=text]/` stay literal.
+    fn snippet(&self, name: &str) -> Option<&'s str> {
+        if self.snippet_stack.iter().any(|expanding| expanding == name) {
+            return None;
         }
+        self.snippets?.get(name)
     }
 
-    fn parse_identifier(&mut self) -> Result<String, EmmetError> {
-        let start = self.position;
+    /// Parses a snippet's abbreviation and merges the element that named it
+    /// into the result.
+    fn expand_snippet(
+        &mut self,
+        name: &str,
+        abbreviation: &'s str,
+        node: BorrowedElement<'s>,
+        parent: Option<&str>,
+    ) -> Result<BorrowedElement<'s>, EmmetError> {
+        let mut snippet_stack = self.snippet_stack.clone();
+        snippet_stack.push(name.to_string());
+
+        let mut parser = EmmetParser {
+            input: abbreviation,
+            tokens: lexer::tokenize(abbreviation),
+            index: 0,
+            climb: 0,
+            depth: self.depth,
+            parent: parent.map(str::to_string),
+            snippets: self.snippets,
+            elements: self.elements,
+            config: self.config,
+            snippet_stack,
+            filters: Vec::new(),
+            operator: None,
+            recover: self.recover,
+            diagnostics: Vec::new(),
+            expected: Vec::new(),
+            expected_final: false,
+        };
+        // Parts written in the snippet point at its name in the abbreviation
+        let name_span = node.spans.tag.unwrap_or_default();
+        let mut nodes = parser.parse_borrowed().map_err(|err| match err {
+            EmmetError::UnknownVariable(_) => err,
+            err => EmmetError::InvalidSyntax(
+                ParseError::new(ErrorCode::InvalidSnippet, name_span, &[], None)
+                    .with_message(format!("in snippet '{}': {}", name, err)),
+            ),
+        })?;
+        for mut diagnostic in parser.diagnostics {
+            diagnostic.span = name_span;
+            diagnostic.message = format!("in snippet '{}': {}", name, diagnostic.message);
+            self.diagnostics.push(diagnostic);
+        }
+        for snippet_node in &mut nodes {
+            snippet_node.relocate(name_span);
+        }
 
-        while self.position < self.input.len() {
-            let ch = self.input.chars().nth(self.position).unwrap();
-            if ch.is_alphanumeric() || ch == '-' || ch == '_' {
-                self.position += 1;
-            } else {
-                break;
+        Ok(merge_snippet(node, nodes))
+    }
+
+    fn parse_tag_name(&mut self) -> Result<Option<Cow<'s, str>>, EmmetError> {
+        let Some(token) = self.eat_numbered(TokenKind::TagName)? else {
+            return Ok(None);
+        };
+        let mut name = Cow::Borrowed(self.text(token));
+
+        // Snippet names such as `ul+` end with the sibling operator; it is
+        // only part of the name where another element cannot follow
+        if self.peek() == Some(TokenKind::Sibling) {
+            let next = self.tokens.get(self.index + 1).map(|token| token.kind);
+            let snippet_name = format!("{}+", name);
+            if matches!(
+                next,
+                None | Some(
+                    TokenKind::Child
+                        | TokenKind::ClimbUp
+                        | TokenKind::Sibling
+                        | TokenKind::GroupEnd
+                        | TokenKind::Asterisk
+                )
+            ) && self.snippet(&snippet_name).is_some()
+            {
+                self.bump();
+                name = Cow::Owned(snippet_name);
             }
         }
 
-        if self.position > start {
-            Ok(self.input[start..self.position].to_string())
-        } else {
-            Err(EmmetError::InvalidSyntax("Expected identifier".to_string()))
+        Ok(Some(name))
+    }
+
+    /// Parses an id, class or attribute name lexed as `kind`. The first of
+    /// `expected` describes the name, the others what may be written
+    /// instead.
+    fn parse_name(
+        &mut self,
+        kind: TokenKind,
+        code: ErrorCode,
+        expected: &[Expected],
+    ) -> Result<Option<Cow<'s, str>>, EmmetError> {
+        match self.eat_numbered(kind)? {
+            Some(token) => {
+                self.expect(&expected[..1]);
+                Ok(Some(Cow::Borrowed(self.text(token))))
+            }
+            None => {
+                let err = self.error_here(code, expected);
+                self.fail(EmmetError::InvalidSyntax(err))?;
+                Ok(None)
+            }
         }
     }
 
-    fn parse_attributes(&mut self) -> Result<Vec<Attribute>, EmmetError> {
+    fn parse_attributes(&mut self) -> Result<Vec<BorrowedAttribute<'s>>, EmmetError> {
         let mut attributes = Vec::new();
-
-        if self.consume_char() != Some('[') {
-            return Err(EmmetError::InvalidAttribute);
-        }
+        self.bump();
 
         loop {
             self.skip_whitespace();
 
-            if self.peek() == Some(']') {
-                self.consume_char();
+            if self.eat(TokenKind::AttributesEnd).is_some() {
                 break;
             }
 
-            let name = self.parse_identifier()?;
-            let value = if self.peek() == Some('=') {
-                self.consume_char();
-                Some(self.parse_attribute_value()?)
-            } else {
-                None
+            let start = self.offset();
+            let name = self.parse_name(
+                TokenKind::AttributeName,
+                ErrorCode::ExpectedName,
+                &[Expected::AttributeName, Expected::Char(']')],
+            )?;
+            let Some(name) = name else {
+                // Recovering: skip to the next attribute
+                if self.peek().is_none() {
+                    break;
+                }
+                while self.peek().is_some_and(|kind| {
+                    !matches!(kind, TokenKind::Whitespace | TokenKind::AttributesEnd)
+                }) {
+                    self.bump();
+                }
+                continue;
+            };
+            let mut spans = AttributeSpans {
+                name: self.span_from(start),
+                value: None,
+            };
+            let mut boolean = false;
+            let value = match self.peek() {
+                Some(TokenKind::Equals) => {
+                    self.bump();
+                    let value_start = self.offset();
+                    let value = self.parse_attribute_value()?;
+                    let span = self.span_from(value_start);
+                    spans.value = Some(span);
+                    Some(self.substitute(value, span)?)
+                }
+                Some(TokenKind::Dot) => {
+                    self.bump();
+                    boolean = true;
+                    None
+                }
+                Some(_) => None,
+                None => {
+                    self.expect(&[Expected::Char('='), Expected::Char('.')]);
+                    None
+                }
             };
 
-            attributes.push(Attribute { name, value });
+            attributes.push(BorrowedAttribute {
+                name,
+                value,
+                boolean,
+                span: self.span_from(start),
+                spans,
+            });
 
             self.skip_whitespace();
-
-            if self.peek() == Some(',') {
-                self.consume_char();
-            }
+            self.eat(TokenKind::Comma);
         }
 
         Ok(attributes)
     }
 
-    fn parse_attribute_value(&mut self) -> Result<String, EmmetError> {
-        if self.peek() == Some('"') {
-            self.consume_char();
-            let start = self.position;
-
-            while self.position < self.input.len() {
-                if self.peek() == Some('"') {
-                    self.consume_char();
-                    return Ok(self.input[start..self.position - 1].to_string());
-                }
-                self.position += 1;
+    fn parse_attribute_value(&mut self) -> Result<Cow<'s, str>, EmmetError> {
+        if let Some(open) = self.eat(TokenKind::Quote) {
+            let quote = self.text(open).chars().next().unwrap_or('"');
+            // A backslash keeps the quote in the value, as in `"say \"hi\""`
+            let value = match self.eat_numbered(TokenKind::AttributeValue)? {
+                Some(value) => unescape(self.text(value), &[quote]),
+                None => Cow::Borrowed(""),
+            };
+            if self.eat(TokenKind::Quote).is_some() {
+                return Ok(value);
             }
 
-            Err(EmmetError::UnclosedBracket)
+            self.expect(&[Expected::AttributeValue]);
+            let start = cursor::offset(open.span);
+            let err = self.unclosed(ErrorCode::UnclosedQuote, start, Expected::Char(quote));
+            self.fail(EmmetError::UnclosedBracket(err))?;
+            Ok(value)
+        } else if let Some(value) = self.eat_numbered(TokenKind::AttributeValue)? {
+            self.expect(&[Expected::AttributeValue]);
+            Ok(Cow::Borrowed(self.text(value)))
         } else {
-            let start = self.position;
+            let err = self.error_here(
+                ErrorCode::ExpectedAttributeValue,
+                &[Expected::AttributeValue],
+            );
+            self.fail(EmmetError::InvalidAttribute(err))?;
+            Ok(Cow::Borrowed(""))
+        }
+    }
 
-            while self.position < self.input.len() {
-                let ch = self.input.chars().nth(self.position).unwrap();
-                if ch.is_alphanumeric() || ch == '-' || ch == '_' || ch == ' ' || ch == '.' {
-                    self.position += 1;
-                } else {
-                    break;
-                }
+    fn parse_text_content(&mut self) -> Result<Cow<'s, str>, EmmetError> {
+        let open = self.offset();
+        self.bump();
+
+        let text = match self.eat_numbered(TokenKind::Text)? {
+            Some(text) => unescape(self.text(text), &['{', '}']),
+            None => Cow::Borrowed(""),
+        };
+        if self.eat(TokenKind::TextEnd).is_some() {
+            return Ok(text);
+        }
+
+        self.expect(&[Expected::Text]);
+        let err = self.unclosed(ErrorCode::UnclosedText, open, Expected::Char('}'));
+        self.fail(EmmetError::UnclosedBracket(err))?;
+        Ok(text)
+    }
+
+    /// Parses a multiplier count. Returns `None` when recovering from a
+    /// missing or invalid count.
+    fn parse_number(&mut self) -> Result<Option<u32>, EmmetError> {
+        let Some(number) = self.eat(TokenKind::Number) else {
+            let err = self.error_here(ErrorCode::InvalidNumber, &[Expected::Number]);
+            self.fail(EmmetError::InvalidSyntax(err))?;
+            return Ok(None);
+        };
+
+        self.expect(&[Expected::Number]);
+        match self.text(number).parse() {
+            Ok(count) => Ok(Some(count)),
+            Err(_) => {
+                let err = self
+                    .error_at(cursor::offset(number.span), ErrorCode::InvalidNumber, &[])
+                    .with_message("number too large".to_string());
+                self.fail(EmmetError::InvalidSyntax(err))?;
+                Ok(None)
             }
+        }
+    }
 
-            if self.position > start {
-                Ok(self.input[start..self.position].to_string())
-            } else {
-                Err(EmmetError::InvalidAttribute)
+    /// Substitutes variables into text or an attribute value written at
+    /// `span`. Unknown variables in strict mode are kept when recovering.
+    fn substitute(&mut self, value: Cow<'s, str>, span: Span) -> Result<Cow<'s, str>, EmmetError> {
+        if !value.contains("${") {
+            return Ok(value);
+        }
+        match self.config.substitute(&value) {
+            Err(EmmetError::UnknownVariable(name)) if self.recover => {
+                let err = ParseError::new(ErrorCode::UnknownVariable, span, &[], None)
+                    .with_message(format!("unknown variable '{}'", name));
+                self.record(err);
+                Ok(value)
             }
+            // Values without variables stay borrowed
+            Ok(substituted) if substituted == value => Ok(value),
+            result => result.map(Cow::Owned),
         }
     }
 
-    fn parse_text_content(&mut self) -> Result<String, EmmetError> {
-        if self.consume_char() != Some('{') {
-            return Err(EmmetError::InvalidSyntax("Expected '{'".to_string()));
+    /// Returns `err`, or in recovery mode records it so that parsing can go
+    /// on.
+    fn fail(&mut self, err: EmmetError) -> Result<(), EmmetError> {
+        let Some(parse_error) = err.parse_error() else {
+            return Err(err);
+        };
+        // Errors at the very end after another error there only repeat it
+        let at_end = parse_error.found.is_none();
+        let repeated = at_end && parse_error.span.is_empty() && self.expected_final;
+        if at_end {
+            let expected = parse_error.expected.clone();
+            self.expect(&expected);
+            self.expected_final = true;
+        }
+        if !self.recover {
+            return Err(err);
         }
+        if !repeated {
+            self.record(parse_error.clone());
+        }
+        Ok(())
+    }
 
-        let start = self.position;
+    /// Records a diagnostic, unless one was already recorded at its span.
+    fn record(&mut self, err: ParseError) {
+        if !self.diagnostics.iter().any(|d| d.span == err.span) {
+            self.diagnostics.push(err);
+        }
+    }
 
-        while self.position < self.input.len() {
-            if self.peek() == Some('}') {
-                self.consume_char();
-                return Ok(self.input[start..self.position - 1].to_string());
+    /// Notes what may be written to continue the input, if parsing reached
+    /// its end.
+    fn expect(&mut self, expected: &[Expected]) {
+        if self.expected_final || self.peek().is_some() {
+            return;
+        }
+        for token in expected {
+            if !self.expected.contains(token) {
+                self.expected.push(*token);
             }
-            self.position += 1;
         }
+    }
 
-        Err(EmmetError::UnclosedBracket)
+    /// Returns the position of the next token, or the end of input.
+    fn offset(&self) -> Offset {
+        match self.tokens.get(self.index) {
+            Some(token) => cursor::offset(token.span),
+            None => match self.tokens.last() {
+                Some(token) => cursor::end_offset(token.span),
+                None => Offset::default(),
+            },
+        }
     }
 
-    fn parse_number(&mut self) -> Result<u32, EmmetError> {
-        let start = self.position;
+    /// Returns the span from `start` to the current position.
+    fn span_from(&self, start: Offset) -> Span {
+        cursor::span(start, self.offset())
+    }
 
-        while self.position < self.input.len() {
-            let ch = self.input.chars().nth(self.position).unwrap();
-            if ch.is_numeric() {
-                self.position += 1;
-            } else {
-                break;
-            }
+    /// Returns an error for the character at the current position.
+    fn error_here(&self, code: ErrorCode, expected: &[Expected]) -> ParseError {
+        self.error_at(self.offset(), code, expected)
+    }
+
+    /// Returns an error for the character at `at`, or for the end of input.
+    fn error_at(&self, at: Offset, code: ErrorCode, expected: &[Expected]) -> ParseError {
+        let (found, span) = cursor::char_at(self.input, at);
+        ParseError::new(code, span, expected, found)
+    }
+
+    /// Returns an error for the bracket at `open` left unclosed at the end
+    /// of input.
+    fn unclosed(&self, code: ErrorCode, open: Offset, expected: Expected) -> ParseError {
+        let (_, span) = cursor::char_at(self.input, open);
+        ParseError::new(code, span, &[expected], None)
+    }
+
+    fn text(&self, token: Token) -> &'s str {
+        &self.input[token.span.byte_range()]
+    }
+
+    fn peek(&self) -> Option<TokenKind> {
+        self.tokens.get(self.index).map(|token| token.kind)
+    }
+
+    fn bump(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).copied();
+        if token.is_some() {
+            self.index += 1;
         }
+        token
+    }
 
-        if self.position > start {
-            self.input[start..self.position]
-                .parse()
-                .map_err(|_| EmmetError::InvalidSyntax("Invalid number".to_string()))
+    /// Consumes the next token if it is of `kind`.
+    fn eat(&mut self, kind: TokenKind) -> Option<Token> {
+        if self.peek() == Some(kind) {
+            self.bump()
         } else {
-            Err(EmmetError::InvalidSyntax("Expected number".to_string()))
+            None
         }
     }
 
-    fn parse_children(&mut self) -> Result<Vec<EmmetElement>, EmmetError> {
-        let mut children = Vec::new();
+    /// Consumes the next token if it is of `kind`, reporting `$@` start
+    /// numbers in it that are too large.
+    fn eat_numbered(&mut self, kind: TokenKind) -> Result<Option<Token>, EmmetError> {
+        let Some(token) = self.eat(kind) else {
+            return Ok(None);
+        };
+        let text = self.text(token);
+        if let Some(digits) = numbering::invalid_start(text) {
+            let start = cursor::offset(token.span);
+            let at = Offset {
+                byte: start.byte + digits.start,
+                char: start.char + text[..digits.start].chars().count(),
+            };
+            let err = self
+                .error_at(at, ErrorCode::InvalidNumber, &[])
+                .with_message("number too large".to_string());
+            self.fail(EmmetError::InvalidSyntax(err))?;
+        }
+        Ok(Some(token))
+    }
 
-        while self.position < self.input.len() {
-            self.skip_whitespace();
+    fn skip_whitespace(&mut self) {
+        self.eat(TokenKind::Whitespace);
+    }
+}
 
-            if self.peek().is_none() || self.peek() == Some('+') {
-                break;
-            }
+/// Removes the backslashes before `\\` and the characters in `escaped`, as
+/// in the `\"` of a quoted value. Other backslashes, such as the one in
+/// `\$`, are kept.
+fn unescape<'s>(text: &'s str, escaped: &[char]) -> Cow<'s, str> {
+    let is_escaped = |ch: char| ch == '\\' || escaped.contains(&ch);
+    if !text
+        .char_indices()
+        .any(|(i, ch)| ch == '\\' && text[i + 1..].starts_with(is_escaped))
+    {
+        return Cow::Borrowed(text);
+    }
 
-            let child = self.parse_element()?;
-            children.push(child);
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match chars.peek() {
+            Some(&next) if ch == '\\' && is_escaped(next) => {
+                output.push(next);
+                chars.next();
+            }
+            _ => output.push(ch),
         }
+    }
+    Cow::Owned(output)
+}
 
-        Ok(children)
+/// Reads snippet text that is one whole comment or a doctype, returning its
+/// kind and content.
+pub(crate) fn markup(text: &str) -> Option<(ElementKind, &str)> {
+    let comment = text
+        .strip_prefix("<!--")
+        .and_then(|text| text.strip_suffix("-->"))
+        .filter(|comment| !comment.contains("-->"));
+    if let Some(comment) = comment {
+        return Some((ElementKind::Comment, comment));
     }
+    text.get(..9)
+        .filter(|start| start.eq_ignore_ascii_case("<!doctype"))
+        .and_then(|_| text[9..].strip_suffix('>'))
+        .filter(|doctype| !doctype.contains(['<', '>']))
+        .map(|doctype| (ElementKind::Doctype, doctype.trim()))
+}
 
-    fn peek(&self) -> Option<char> {
-        if self.position < self.input.len() {
-            self.input.chars().nth(self.position)
-        } else {
-            None
+/// Merges the element that named a snippet into the snippet's parsed nodes.
+///
+/// Id, classes and attributes go to the first element of the snippet, with
+/// attributes of the same name replaced. Text fills the snippet's `${0}`
+/// field if it has one and otherwise replaces the first element's text. A
+/// multiplier repeats the whole snippet. The snippet's fields are numbered
+/// after those of the element, so that fields from both stay apart.
+fn merge_snippet<'s>(
+    mut node: BorrowedElement<'s>,
+    mut nodes: Vec<BorrowedElement<'s>>,
+) -> BorrowedElement<'s> {
+    let mut offset = 0;
+    node.map_values(&mut |value| {
+        renumber_fields(value, &mut |number| {
+            offset = offset.max(number);
+            number
+        });
+        None
+    });
+    if offset > 0 {
+        for snippet_node in &mut nodes {
+            snippet_node.map_values(&mut |value| {
+                value.contains("${").then(|| {
+                    renumber_fields(value, &mut |number| match number {
+                        // `${0}` is where the element's text goes
+                        0 => 0,
+                        number => number + offset,
+                    })
+                })
+            });
         }
     }
 
-    fn consume_char(&mut self) -> Option<char> {
-        if self.position < self.input.len() {
-            let ch = self.input.chars().nth(self.position).unwrap();
-            self.position += 1;
-            Some(ch)
-        } else {
-            None
+    let mut text = node.text;
+
+    if let Some(value) = &text {
+        let slot = nodes
+            .iter_mut()
+            .find(|n| n.text.as_deref().is_some_and(|t| t.contains("${0}")));
+        if let Some(slot) = slot {
+            slot.text = slot
+                .text
+                .as_ref()
+                .map(|t| Cow::Owned(t.replace("${0}", value)));
+            slot.spans.text = node.spans.text;
+            text = None;
         }
     }
 
-    fn skip_whitespace(&mut self) {
-        while self.position < self.input.len() {
-            let ch = self.input.chars().nth(self.position).unwrap();
-            if ch.is_whitespace() {
-                self.position += 1;
-            } else {
-                break;
+    if let Some(target) = nodes.iter_mut().find(|n| n.kind == ElementKind::Element) {
+        if node.id.is_some() {
+            target.id = node.id;
+            target.spans.id = node.spans.id;
+        }
+        target.classes.extend(node.classes);
+        target.spans.classes.extend(node.spans.classes);
+        for attr in node.attributes {
+            match target.attributes.iter_mut().find(|a| a.name == attr.name) {
+                Some(existing) => *existing = attr,
+                None => target.attributes.push(attr),
             }
         }
+        if text.is_some() {
+            target.text = text;
+            target.spans.text = node.spans.text;
+        }
+        target.self_closing |= node.self_closing;
+    }
+
+    let mut result = if nodes.len() == 1 {
+        nodes.pop().unwrap()
+    } else {
+        BorrowedElement::group(nodes)
+    };
+
+    if node.multiplier.is_some() {
+        if result.multiplier.is_some() {
+            result = BorrowedElement::group(vec![result]);
+        }
+        result.multiplier = node.multiplier;
+        result.spans.multiplier = node.spans.multiplier;
     }
+
+    result
 }
 
 pub fn parse_emmet(input: &str) -> Result<Vec<EmmetElement>, EmmetError> {
@@ -388,11 +1325,47 @@ pub fn parse_emmet(input: &str) -> Result<Vec<EmmetElement>, EmmetError> {
     parser.parse()
 }
 
+/// Parses an abbreviation expanded inside an element with the given tag, so
+/// that implicit tag names resolve against it.
+pub fn parse_emmet_with_parent(input: &str, parent: &str) -> Result<Vec<EmmetElement>, EmmetError> {
+    let mut parser = EmmetParser::new(input).with_parent(parent);
+    parser.parse()
+}
+
+/// Parses an abbreviation with the variables and options of `config`.
+pub fn parse_emmet_with_config(
+    input: &str,
+    config: &ExpandConfig,
+) -> Result<Vec<EmmetElement>, EmmetError> {
+    let mut parser = EmmetParser::new(input).with_config(config);
+    parser.parse()
+}
+
+/// Converts Emmet syntax to HTML. A trailing `|e` filter escapes text
+/// content.
 pub fn emmet_to_html(input: &str) -> Result<String, EmmetError> {
-    let elements = parse_emmet(input)?;
-    let html: String = elements.iter().map(|e| e.to_html()).collect();
+    let (elements, renderer) = parse_with_filters(input)?;
+    let mut html = String::new();
+    renderer
+        .render_to(&elements, &mut html)
+        .expect("writing to a String does not fail");
     Ok(html)
 }
 
+/// Converts Emmet syntax to an editor snippet, with fields numbered across
+/// all top-level elements and written by `formatter`.
+pub fn emmet_to_snippet(input: &str, formatter: &dyn FieldFormatter) -> Result<String, EmmetError> {
+    let (elements, renderer) = parse_with_filters(input)?;
+    Ok(renderer.with_fields(formatter).render(&elements))
+}
+
+/// Parses `input` along with a renderer applying its filters.
+fn parse_with_filters(input: &str) -> Result<(Vec<EmmetElement>, Renderer<'static>), EmmetError> {
+    let mut parser = EmmetParser::new(input);
+    let elements = parser.parse()?;
+    let escape = parser.filters().iter().any(|filter| filter == "e");
+    Ok((elements, Renderer::new().escape_text(escape)))
+}
+
 #[cfg(test)]
 mod tests;
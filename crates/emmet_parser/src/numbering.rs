@@ -0,0 +1,124 @@
+use std::ops::Range;
+
+/// Position of an element within the repetition produced by the nearest
+/// multiplier, used to resolve `$` markers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Repeat {
+    pub index: u32,
+    pub count: u32,
+}
+
+impl Repeat {
+    /// Replaces every numbering marker in `input`.
+    ///
+    /// A run of `$` is replaced by the repetition number zero-padded to the
+    /// length of the run. It may be followed by `@` with an optional `-` to
+    /// count down and an optional start number, as in `$$@-3`. An escaped
+    /// `\$` and the `$` opening a `${...}` field are kept as written; the
+    /// escape is removed when the text is output. Markers whose start
+    /// number does not fit a `u32`, which the parser reports, are kept as
+    /// written too.
+    pub fn apply(&self, input: &str) -> String {
+        let mut output = String::with_capacity(input.len());
+        let mut written = 0;
+
+        for (offset, marker) in markers(input) {
+            let end = offset + marker.len;
+            output.push_str(&input[written..offset]);
+            written = end;
+            let Some(start) = marker.start() else {
+                output.push_str(&input[offset..end]);
+                continue;
+            };
+
+            // Counted in `u64` so that numbers past `u32::MAX` are written
+            // out rather than wrapping
+            let position = if marker.reverse {
+                self.count - 1 - self.index
+            } else {
+                self.index
+            };
+            let number = u64::from(start) + u64::from(position);
+            output.push_str(&format!("{:0width$}", number, width = marker.width));
+        }
+
+        output.push_str(&input[written..]);
+        output
+    }
+}
+
+/// A `$` numbering marker, as in `$$@-3`.
+struct Marker<'a> {
+    /// The number of `$`.
+    width: usize,
+    reverse: bool,
+    /// The start number written after `@`, if any.
+    digits: &'a str,
+    /// The length of the marker in bytes.
+    len: usize,
+}
+
+impl Marker<'_> {
+    /// The number of the first repetition, or `None` if it does not fit a
+    /// `u32`.
+    fn start(&self) -> Option<u32> {
+        match self.digits {
+            "" => Some(1),
+            digits => digits.parse().ok(),
+        }
+    }
+}
+
+/// Lists the numbering markers in `input` with their byte offsets,
+/// skipping escaped `\$` and the `$` opening a `${...}` field.
+fn markers(input: &str) -> impl Iterator<Item = (usize, Marker<'_>)> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        while let Some(found) = input[offset..].find(['\\', '$']) {
+            let at = offset + found;
+            let rest = &input[at..];
+            if rest.starts_with("\\$") || rest.starts_with("${") {
+                offset = at + 2;
+                continue;
+            }
+            if rest.starts_with('\\') {
+                offset = at + 1;
+                continue;
+            }
+
+            let width = rest.len() - rest.trim_start_matches('$').len();
+            let mut marker = Marker {
+                width,
+                reverse: false,
+                digits: "",
+                len: width,
+            };
+            if rest[marker.len..].starts_with('@') {
+                marker.len += 1;
+                if rest[marker.len..].starts_with('-') {
+                    marker.reverse = true;
+                    marker.len += 1;
+                }
+                let digits = rest[marker.len..]
+                    .find(|ch: char| !ch.is_ascii_digit())
+                    .unwrap_or(rest.len() - marker.len);
+                marker.digits = &rest[marker.len..marker.len + digits];
+                marker.len += digits;
+            }
+            offset = at + marker.len;
+            return Some((at, marker));
+        }
+        None
+    })
+}
+
+/// Finds the first `$@` start number in `input` that does not fit a
+/// `u32`, returning the byte range of its digits.
+pub(crate) fn invalid_start(input: &str) -> Option<Range<usize>> {
+    markers(input)
+        .find(|(_, marker)| marker.start().is_none())
+        .map(|(offset, marker)| {
+            let end = offset + marker.len;
+            end - marker.digits.len()..end
+        })
+}
@@ -0,0 +1,230 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::elements::builtin_elements;
+use crate::{
+    Attribute, ElementKind, EmmetElement, EmmetError, EmmetParser, ErrorCode, Expected, ParseError,
+    Span,
+};
+
+/// Writes elements back as an abbreviation, the inverse of parsing without
+/// snippets.
+///
+/// The output is canonical: parts come in the order tag, id, classes,
+/// attributes, `/`, text and multiplier, and siblings after children are
+/// joined with `^`. Tags that the parent implies, like `li` in `ul>.item`,
+/// are left out, attribute values are quoted only when needed, braces in
+/// unbalanced text are escaped, and so are backslashes that would escape
+/// what follows them. The comments and doctypes of snippets are written as
+/// the text they stand for, which reads back as text.
+///
+/// ```
+/// use emmet_parser::{parse_emmet, to_abbreviation};
+///
+/// let elements = parse_emmet("ul>li.item*2>b[title='Hi there']+span").unwrap();
+/// assert_eq!(to_abbreviation(&elements), "ul>.item*2>b[title=\"Hi there\"]+span");
+/// ```
+pub fn to_abbreviation(elements: &[EmmetElement]) -> String {
+    let mut output = String::new();
+    write_siblings(&mut output, elements, None);
+    output
+}
+
+impl EmmetElement {
+    /// Writes the element and its children as an abbreviation. See
+    /// [`to_abbreviation`].
+    pub fn to_abbreviation(&self) -> String {
+        to_abbreviation(std::slice::from_ref(self))
+    }
+}
+
+impl fmt::Display for EmmetElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_abbreviation())
+    }
+}
+
+/// Parses an abbreviation without expanding snippets, the inverse of
+/// [`Display`](fmt::Display). Several top-level elements are returned as a
+/// group.
+impl FromStr for EmmetElement {
+    type Err = EmmetError;
+
+    fn from_str(input: &str) -> Result<Self, EmmetError> {
+        let mut elements = EmmetParser::new(input).without_snippets().parse()?;
+        match elements.len() {
+            0 => {
+                let end = input.chars().count();
+                let span = Span {
+                    start: input.len(),
+                    end: input.len(),
+                    char_start: end,
+                    char_end: end,
+                };
+                let err =
+                    ParseError::new(ErrorCode::ExpectedElement, span, &[Expected::Element], None);
+                Err(EmmetError::InvalidSyntax(err))
+            }
+            1 => Ok(elements.pop().unwrap()),
+            _ => Ok(EmmetElement::group(elements)),
+        }
+    }
+}
+
+/// Writes `elements` as siblings inside `parent`. Returns how many levels
+/// below the siblings the output ends, which the next sibling of an
+/// enclosing element climbs back with `^`.
+fn write_siblings(output: &mut String, elements: &[EmmetElement], parent: Option<&str>) -> usize {
+    let mut depth = 0;
+
+    for (i, element) in elements.iter().enumerate() {
+        if i > 0 {
+            match depth {
+                0 => output.push('+'),
+                depth => output.push_str(&"^".repeat(depth)),
+            }
+        }
+        depth = write_element(output, element, parent);
+    }
+
+    depth
+}
+
+fn write_element(output: &mut String, element: &EmmetElement, parent: Option<&str>) -> usize {
+    if element.is_group() {
+        output.push('(');
+        write_siblings(output, &element.children, parent);
+        output.push(')');
+        write_multiplier(output, element);
+        return 0;
+    }
+
+    let has_parts =
+        element.id.is_some() || !element.classes.is_empty() || !element.attributes.is_empty();
+    let implied = has_parts && element.tag == builtin_elements().implicit_tag(parent);
+    if element.kind == ElementKind::Element && !implied {
+        output.push_str(&element.tag);
+    }
+    if let Some(id) = &element.id {
+        output.push('#');
+        output.push_str(id);
+    }
+    for class in &element.classes {
+        output.push('.');
+        output.push_str(class);
+    }
+    if !element.attributes.is_empty() {
+        output.push('[');
+        for (i, attribute) in element.attributes.iter().enumerate() {
+            if i > 0 {
+                output.push(' ');
+            }
+            // A plain value would run on into a following valueless
+            // attribute, as the space may be part of the value
+            let next = element.attributes.get(i + 1);
+            let quote = next.is_some_and(|next| next.value.is_none());
+            write_attribute(output, attribute, quote);
+        }
+        output.push(']');
+    }
+    if element.self_closing {
+        output.push('/');
+    }
+    if let Some(text) = &element.text {
+        output.push('{');
+        match element.kind {
+            ElementKind::Comment => output.push_str(&escape_text(&format!("<!--{}-->", text))),
+            ElementKind::Doctype => output.push_str(&escape_text(&format!("<!DOCTYPE {}>", text))),
+            _ => output.push_str(&escape_text(text)),
+        }
+        output.push('}');
+    }
+    write_multiplier(output, element);
+
+    if element.children.is_empty() {
+        return 0;
+    }
+    output.push('>');
+    let parent = (element.kind == ElementKind::Element).then_some(element.tag.as_str());
+    write_siblings(output, &element.children, parent) + 1
+}
+
+fn write_multiplier(output: &mut String, element: &EmmetElement) {
+    if let Some(count) = element.multiplier {
+        output.push('*');
+        output.push_str(&count.to_string());
+    }
+}
+
+fn write_attribute(output: &mut String, attribute: &Attribute, quote: bool) {
+    output.push_str(&attribute.name);
+    match &attribute.value {
+        Some(value) if !quote && is_plain_value(value) => {
+            output.push('=');
+            output.push_str(value);
+        }
+        Some(value) => {
+            let quote = if value.contains('"') && !value.contains('\'') {
+                '\''
+            } else {
+                '"'
+            };
+            output.push('=');
+            output.push(quote);
+            output.push_str(
+                &escape_backslashes(value, &[quote]).replace(quote, &format!("\\{}", quote)),
+            );
+            output.push(quote);
+        }
+        None if attribute.boolean => output.push('.'),
+        None => {}
+    }
+}
+
+/// Whether `value` reads back the same without quotes.
+fn is_plain_value(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || matches!(ch, '-' | '_'))
+}
+
+/// Escapes the braces of `text` unless they are balanced, as in the
+/// `${1:name}` of fields.
+fn escape_text(text: &str) -> String {
+    let mut depth = 0usize;
+    let balanced = text.chars().all(|ch| match ch {
+        '{' => {
+            depth += 1;
+            true
+        }
+        '}' => depth.checked_sub(1).map(|d| depth = d).is_some(),
+        _ => true,
+    }) && depth == 0;
+
+    let text = escape_backslashes(text, &['{', '}']);
+    if balanced {
+        text
+    } else {
+        text.replace('{', "\\{").replace('}', "\\}")
+    }
+}
+
+/// Doubles the backslashes that would otherwise escape what follows them:
+/// another backslash, one of `escaped`, or the closing quote or brace at
+/// the end.
+fn escape_backslashes(text: &str, escaped: &[char]) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        output.push(ch);
+        let escapes = match chars.peek() {
+            Some(&next) => next == '\\' || escaped.contains(&next),
+            None => true,
+        };
+        if ch == '\\' && escapes {
+            output.push('\\');
+        }
+    }
+    output
+}
@@ -0,0 +1,144 @@
+use std::borrow::Cow;
+
+/// How elements without content are closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfClosingStyle {
+    /// `<br>`
+    Html,
+    /// `<br />`
+    Xhtml,
+    /// `<br/>`, and every element without content self-closes.
+    Xml,
+}
+
+/// Letter case applied to tag or attribute names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Case {
+    #[default]
+    Preserve,
+    Lower,
+    Upper,
+}
+
+impl Case {
+    pub fn apply(self, name: &str) -> String {
+        self.convert(name).into_owned()
+    }
+
+    /// Applies the case, borrowing `name` when it is preserved.
+    pub(crate) fn convert(self, name: &str) -> Cow<'_, str> {
+        match self {
+            Case::Preserve => Cow::Borrowed(name),
+            Case::Lower => Cow::Owned(name.to_lowercase()),
+            Case::Upper => Cow::Owned(name.to_uppercase()),
+        }
+    }
+}
+
+/// The quote character around attribute values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quote {
+    /// `"`
+    #[default]
+    Double,
+    /// `'`
+    Single,
+}
+
+impl Quote {
+    pub fn as_char(self) -> char {
+        match self {
+            Quote::Double => '"',
+            Quote::Single => '\'',
+        }
+    }
+}
+
+/// Markup dialect details of the rendered output.
+///
+/// ```
+/// use emmet_parser::{parse_emmet, Profile, Renderer};
+///
+/// let elements = parse_emmet("br+div").unwrap();
+/// let render = |profile| Renderer::new().with_profile(profile).render(&elements);
+///
+/// assert_eq!(render(Profile::html()), "<br><div></div>");
+/// assert_eq!(render(Profile::xhtml()), "<br /><div></div>");
+/// assert_eq!(render(Profile::xml()), "<br/><div/>");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Profile {
+    pub self_closing: SelfClosingStyle,
+    pub tag_case: Case,
+    pub attribute_case: Case,
+    pub quote: Quote,
+}
+
+impl Default for Profile {
+    /// The XHTML profile, matching [`EmmetElement::to_html`](crate::EmmetElement::to_html).
+    fn default() -> Self {
+        Self::xhtml()
+    }
+}
+
+impl Profile {
+    /// HTML5: void elements end with `>`.
+    pub const fn html() -> Self {
+        Self {
+            self_closing: SelfClosingStyle::Html,
+            ..Self::xhtml()
+        }
+    }
+
+    /// XHTML: void elements end with ` />`.
+    pub const fn xhtml() -> Self {
+        Self {
+            self_closing: SelfClosingStyle::Xhtml,
+            tag_case: Case::Preserve,
+            attribute_case: Case::Preserve,
+            quote: Quote::Double,
+        }
+    }
+
+    /// XML: every element without content ends with `/>`.
+    pub const fn xml() -> Self {
+        Self {
+            self_closing: SelfClosingStyle::Xml,
+            ..Self::xhtml()
+        }
+    }
+
+    /// The profile for a syntax name: `xhtml` and `jsx` use XHTML, `xml` and
+    /// `xsl` use XML, and anything else HTML5.
+    pub fn for_syntax(syntax: &str) -> Self {
+        match syntax.to_ascii_lowercase().as_str() {
+            "xhtml" | "jsx" => Self::xhtml(),
+            "xml" | "xsl" => Self::xml(),
+            _ => Self::html(),
+        }
+    }
+
+    pub fn with_tag_case(mut self, case: Case) -> Self {
+        self.tag_case = case;
+        self
+    }
+
+    pub fn with_attribute_case(mut self, case: Case) -> Self {
+        self.attribute_case = case;
+        self
+    }
+
+    pub fn with_quote(mut self, quote: Quote) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// The end of a self-closing tag.
+    pub(crate) fn self_closing_end(&self) -> &'static str {
+        match self.self_closing {
+            SelfClosingStyle::Html => ">",
+            SelfClosingStyle::Xhtml => " />",
+            SelfClosingStyle::Xml => "/>",
+        }
+    }
+}
@@ -0,0 +1,479 @@
+use std::borrow::Cow;
+use std::{fmt, io};
+
+use crate::elements::builtin_elements;
+use crate::escape::{write_attribute, write_comment, write_text};
+use crate::expand::{number, Expander, Pending, Siblings};
+use crate::fields::{FieldScope, NO_MARKERS};
+use crate::{
+    ElementTable, EmmetElement, ExpandedElement, ExpandedNode, FieldFormatter, FormatOptions,
+    Profile, SelfClosingStyle,
+};
+
+/// Renders parsed elements as HTML.
+///
+/// By default everything goes on one line and editor fields are replaced by
+/// their placeholders:
+///
+/// ```
+/// use emmet_parser::{parse_emmet, FormatOptions, Renderer};
+///
+/// let elements = parse_emmet("ul>li*2").unwrap();
+/// assert_eq!(Renderer::new().render(&elements), "<ul><li></li><li></li></ul>");
+///
+/// let format = FormatOptions::default();
+/// assert_eq!(
+///     Renderer::new().with_format(&format).render(&elements),
+///     "<ul>\n\t<li></li>\n\t<li></li>\n</ul>"
+/// );
+/// ```
+#[derive(Clone, Copy)]
+pub struct Renderer<'r> {
+    pub(crate) fields: &'r dyn FieldFormatter,
+    format: Option<&'r FormatOptions>,
+    profile: Profile,
+    pub(crate) elements: &'r ElementTable,
+    escape_text: bool,
+}
+
+impl Default for Renderer<'_> {
+    fn default() -> Self {
+        Self {
+            fields: &NO_MARKERS,
+            format: None,
+            profile: Profile::default(),
+            elements: builtin_elements(),
+            escape_text: false,
+        }
+    }
+}
+
+/// A node to write: an expanded node, or a node of the abbreviation that is
+/// expanded as it is written.
+#[derive(Clone, Copy)]
+enum Item<'a> {
+    Expanded(&'a ExpandedNode),
+    Pending(Pending<'a>),
+}
+
+impl<'a> Item<'a> {
+    /// The tag of an element.
+    fn tag(&self) -> Option<Cow<'a, str>> {
+        match *self {
+            Item::Expanded(ExpandedNode::Element(element)) => Some(Cow::Borrowed(&element.tag)),
+            Item::Pending(Pending::Element(element, None)) => Some(Cow::Borrowed(&element.tag)),
+            Item::Pending(Pending::Element(element, repeat)) => {
+                Some(Cow::Owned(number(&element.tag, repeat)))
+            }
+            _ => None,
+        }
+    }
+
+    fn children(&self) -> Items<'a> {
+        match *self {
+            Item::Expanded(ExpandedNode::Element(element)) => {
+                Items::Expanded(element.children.iter())
+            }
+            Item::Pending(Pending::Element(element, repeat)) => {
+                Items::Pending(Siblings::children(element, repeat))
+            }
+            _ => Items::Expanded([].iter()),
+        }
+    }
+}
+
+/// Sibling nodes to write, produced one at a time. Cloning looks ahead.
+#[derive(Clone)]
+enum Items<'a> {
+    Expanded(std::slice::Iter<'a, ExpandedNode>),
+    Pending(Siblings<'a>),
+}
+
+impl<'a> Items<'a> {
+    fn is_empty(&self) -> bool {
+        self.clone().next().is_none()
+    }
+}
+
+impl<'a> Iterator for Items<'a> {
+    type Item = Item<'a>;
+
+    fn next(&mut self) -> Option<Item<'a>> {
+        match self {
+            Items::Expanded(nodes) => nodes.next().map(Item::Expanded),
+            Items::Pending(nodes) => nodes.next().map(Item::Pending),
+        }
+    }
+}
+
+/// Where the first of a list of siblings is written.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum First {
+    /// At the very start of the output, never on a new line.
+    Start,
+    /// After an opening tag or text, on a new line only if it breaks.
+    Inline,
+    /// On a new line, as the content of an element with indented content.
+    NewLine,
+}
+
+impl<'r> Renderer<'r> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes editor fields with `fields`, for output inserted as an editor
+    /// snippet.
+    pub fn with_fields(mut self, fields: &'r dyn FieldFormatter) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// Pretty-prints the output instead of writing it on one line.
+    pub fn with_format(mut self, format: &'r FormatOptions) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Renders for the markup dialect of `profile` instead of XHTML.
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Looks up void and inline elements and default attributes in
+    /// `elements` instead of the HTML element table.
+    pub fn with_elements(mut self, elements: &'r ElementTable) -> Self {
+        self.elements = elements;
+        self
+    }
+
+    /// Escapes `&`, `<` and `>` in text content, like Emmet's `|e` filter.
+    /// Attribute values are always escaped.
+    pub fn escape_text(mut self, escape: bool) -> Self {
+        self.escape_text = escape;
+        self
+    }
+
+    /// Renders `elements` as siblings, with editor fields numbered across
+    /// all of them.
+    pub fn render(&self, elements: &[EmmetElement]) -> String {
+        let mut html = String::new();
+        self.render_to(elements, &mut html)
+            .expect("writing to a String does not fail");
+        html
+    }
+
+    /// Renders every repetition of a single element.
+    pub fn render_element(&self, element: &EmmetElement) -> String {
+        self.render(std::slice::from_ref(element))
+    }
+
+    /// Renders expanded nodes as siblings. Their editor fields were written
+    /// when they were expanded. See [`expand`](Self::expand).
+    pub fn render_nodes(&self, nodes: &[ExpandedNode]) -> String {
+        let mut html = String::new();
+        self.render_nodes_to(nodes, &mut html)
+            .expect("writing to a String does not fail");
+        html
+    }
+
+    /// Expands `elements` into the nodes this renderer renders them as,
+    /// with editor fields numbered across all of them and written by its
+    /// field formatter. See [`expand`](crate::expand).
+    ///
+    /// ```
+    /// use emmet_parser::{parse_emmet, LspFields, Renderer};
+    ///
+    /// let elements = parse_emmet("a:link").unwrap();
+    /// let a = &Renderer::new().with_fields(&LspFields).expand(&elements)[0];
+    /// assert_eq!(a.as_element().unwrap().attribute("href"), Some("http://${1}"));
+    /// ```
+    pub fn expand(&self, elements: &[EmmetElement]) -> Vec<ExpandedNode> {
+        let mut expanded = Vec::new();
+        Expander::new(self).expand(Siblings::new(elements, None), None, &mut expanded);
+        expanded
+    }
+
+    /// Renders `elements` into `out` as it goes: each element is expanded
+    /// as it is written, without building the expanded tree or the output
+    /// in memory first.
+    ///
+    /// ```
+    /// use std::fmt::Write;
+    /// use emmet_parser::{parse_emmet, Renderer};
+    ///
+    /// let mut html = String::from("<body>");
+    /// Renderer::new().render_to(&parse_emmet("p*2").unwrap(), &mut html).unwrap();
+    /// write!(html, "</body>").unwrap();
+    /// assert_eq!(html, "<body><p></p><p></p></body>");
+    /// ```
+    pub fn render_to(&self, elements: &[EmmetElement], out: &mut impl fmt::Write) -> fmt::Result {
+        let items = Items::Pending(Siblings::new(elements, None));
+        let mut expander = Expander::new(self);
+        self.write_siblings(items, 0, First::Start, &mut expander, None, out)
+    }
+
+    /// Renders expanded nodes into `out`. See [`render_to`](Self::render_to).
+    pub fn render_nodes_to(
+        &self,
+        nodes: &[ExpandedNode],
+        out: &mut impl fmt::Write,
+    ) -> fmt::Result {
+        let items = Items::Expanded(nodes.iter());
+        let mut expander = Expander::new(self);
+        self.write_siblings(items, 0, First::Start, &mut expander, None, out)
+    }
+
+    /// Renders `elements` into an [`io::Write`] such as a file or socket,
+    /// as [`render_to`](Self::render_to) does. Wrap unbuffered writers in
+    /// a [`BufWriter`](std::io::BufWriter), as the output is written in
+    /// small pieces.
+    pub fn render_to_io(
+        &self,
+        elements: &[EmmetElement],
+        out: &mut impl io::Write,
+    ) -> io::Result<()> {
+        let mut adapter = IoAdapter { out, error: None };
+        match self.render_to(elements, &mut adapter) {
+            Ok(()) => Ok(()),
+            Err(fmt::Error) => Err(adapter
+                .error
+                .unwrap_or_else(|| io::Error::other("formatting failed"))),
+        }
+    }
+
+    /// Whether element `tag` is closed without content, as void elements
+    /// are, or in XML any element that is `empty`.
+    pub(crate) fn closes(&self, tag: &str, self_closing: bool, empty: bool) -> bool {
+        self_closing
+            || match self.profile.self_closing {
+                SelfClosingStyle::Xml => empty,
+                _ => self.elements.is_void(tag),
+            }
+    }
+
+    /// Writes sibling nodes at `depth`, each on a new line if it breaks.
+    /// Text uses the field `scope` of the element it is in.
+    fn write_siblings(
+        &self,
+        items: Items,
+        depth: usize,
+        first: First,
+        expander: &mut Expander,
+        mut scope: Option<&mut FieldScope>,
+        out: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        for (index, (item, breaks)) in self.lines(items).enumerate() {
+            let newline = match (index, first) {
+                (0, First::Start) => false,
+                (0, First::NewLine) => true,
+                _ => breaks,
+            };
+            if newline {
+                self.newline(depth, out)?;
+            }
+            self.write_item(item, depth, expander, scope.as_deref_mut(), out)?;
+        }
+        Ok(())
+    }
+
+    fn write_item(
+        &self,
+        item: Item,
+        depth: usize,
+        expander: &mut Expander,
+        scope: Option<&mut FieldScope>,
+        out: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        let (element, repeat) = match item {
+            Item::Expanded(ExpandedNode::Element(element)) => {
+                let children = Items::Expanded(element.children.iter());
+                return self.write_element(element, children, depth, expander, None, out);
+            }
+            Item::Expanded(node) => return self.write_leaf(node, out),
+            Item::Pending(Pending::Text(text, repeat)) => {
+                let mut own_scope = FieldScope::new();
+                let scope = scope.unwrap_or(&mut own_scope);
+                return self.write_leaf(&expander.text(text, repeat, scope), out);
+            }
+            Item::Pending(Pending::Markup(markup, repeat)) => {
+                let mut own_scope = FieldScope::new();
+                let scope = scope.unwrap_or(&mut own_scope);
+                return self.write_leaf(&expander.markup(markup, repeat, scope), out);
+            }
+            Item::Pending(Pending::Element(element, repeat)) => (element, repeat),
+        };
+
+        // Fields are shared within an element, not with its parent
+        let mut scope = FieldScope::new();
+        let head = expander.element(element, repeat, &mut scope);
+        let mut children = Items::Pending(Siblings::children(element, repeat));
+        let content = match children.is_empty() {
+            true => expander.content(&head).map(ExpandedNode::Text),
+            false => None,
+        };
+        if let Some(content) = &content {
+            children = Items::Expanded(std::slice::from_ref(content).iter());
+        }
+        self.write_element(&head, children, depth, expander, Some(&mut scope), out)
+    }
+
+    /// Writes text, a comment or a doctype.
+    fn write_leaf(&self, node: &ExpandedNode, out: &mut dyn fmt::Write) -> fmt::Result {
+        match node {
+            ExpandedNode::Text(text) if self.escape_text => write_text(out, text),
+            ExpandedNode::Text(text) => out.write_str(text),
+            ExpandedNode::Comment(comment) => {
+                out.write_str("<!--")?;
+                write_comment(out, comment)?;
+                out.write_str("-->")
+            }
+            ExpandedNode::Doctype(doctype) => write!(out, "<!DOCTYPE {}>", doctype),
+            ExpandedNode::Element(_) => unreachable!("elements are written with their children"),
+        }
+    }
+
+    /// Writes an element with `children` as its content.
+    fn write_element(
+        &self,
+        node: &ExpandedElement,
+        children: Items,
+        depth: usize,
+        expander: &mut Expander,
+        scope: Option<&mut FieldScope>,
+        out: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        let profile = &self.profile;
+        let tag = profile.tag_case.convert(&node.tag);
+
+        // Opening tag
+        write!(out, "<{}", tag)?;
+
+        // Attributes
+        for attr in &node.attributes {
+            let name = profile.attribute_case.convert(&attr.name);
+            if attr.is_boolean() {
+                write!(out, " {}", name)?;
+                continue;
+            }
+            let quote = profile.quote.as_char();
+            write!(out, " {}={}", name, quote)?;
+            write_attribute(out, attr.value.as_deref().unwrap_or(""), quote)?;
+            out.write_char(quote)?;
+        }
+
+        if self.closes(&node.tag, node.self_closing, children.is_empty()) {
+            return out.write_str(profile.self_closing_end());
+        }
+
+        out.write_char('>')?;
+
+        let indented = self.format.is_some_and(|format| {
+            format.forces_indent(&tag) || self.lines(children.clone()).any(|(_, breaks)| breaks)
+        });
+
+        if children.is_empty() {
+            if indented {
+                self.newline(depth + 1, out)?;
+            }
+        } else {
+            let first = if indented {
+                First::NewLine
+            } else {
+                First::Inline
+            };
+            self.write_siblings(children, depth + 1, first, expander, scope, out)?;
+        }
+        if indented {
+            self.newline(depth, out)?;
+        }
+
+        // Closing tag
+        write!(out, "</{}>", tag)
+    }
+
+    /// Pairs each of `items` with whether it starts a new line.
+    fn lines<'a>(&'a self, mut items: Items<'a>) -> impl Iterator<Item = (Item<'a>, bool)> + 'a {
+        let mut index = 0;
+        // Number of inline nodes right before the next one
+        let mut before = 0;
+        std::iter::from_fn(move || {
+            let item = items.next()?;
+            let breaks = self.breaks(&item, index, before, &items);
+            index += 1;
+            before = match self.format {
+                Some(format) if self.is_inline(format, &item) => before + 1,
+                _ => 0,
+            };
+            Some((item, breaks))
+        })
+    }
+
+    /// Whether `item` starts a new line, at `index` among its siblings
+    /// after `before` inline ones and followed by `after`.
+    fn breaks(&self, item: &Item, index: usize, before: usize, after: &Items) -> bool {
+        let Some(format) = self.format else {
+            return false;
+        };
+
+        if !self.is_inline(format, item) {
+            return true;
+        }
+        if index > 0 && before == 0 {
+            return true;
+        }
+
+        if format.inline_break > 0 {
+            let after = after
+                .clone()
+                .take(format.inline_break)
+                .take_while(|item| self.is_inline(format, item))
+                .count();
+            if before + 1 + after >= format.inline_break {
+                return true;
+            }
+        }
+
+        // An inline element holding block content
+        self.has_block(format, item)
+    }
+
+    fn is_inline(&self, format: &FormatOptions, item: &Item) -> bool {
+        item.tag()
+            .is_none_or(|tag| self.elements.is_inline(&tag) || format.is_inline(&tag))
+    }
+
+    /// Whether any descendant of `item` is a block element.
+    fn has_block(&self, format: &FormatOptions, item: &Item) -> bool {
+        item.children()
+            .any(|child| !self.is_inline(format, &child) || self.has_block(format, &child))
+    }
+
+    fn newline(&self, depth: usize, out: &mut dyn fmt::Write) -> fmt::Result {
+        if let Some(format) = self.format {
+            out.write_str(&format.newline)?;
+            out.write_str(&format.base_indent)?;
+            for _ in 0..depth {
+                out.write_str(&format.indent)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes formatted output into an [`io::Write`], keeping the I/O error
+/// that [`fmt::Error`] cannot carry.
+struct IoAdapter<'w, W: io::Write> {
+    out: &'w mut W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Standard Emmet HTML snippets. Keys may list several aliases separated by
+/// `|`; values are abbreviations themselves and may refer to other snippets.
+const HTML_SNIPPETS: &[(&str, &str)] = &[
+    ("a", "a[href]"),
+    (
+        "a:blank",
+        r#"a[href="http://${0}"][target=_blank][rel="noopener noreferrer"]"#,
+    ),
+    ("a:link", r#"a[href="http://${0}"]"#),
+    ("a:mail", r#"a[href="mailto:${0}"]"#),
+    ("a:tel", r#"a[href="tel:+${0}"]"#),
+    ("abbr", "abbr[title]"),
+    ("acr|acronym", "acronym[title]"),
+    ("base", "base[href]/"),
+    ("basefont", "basefont/"),
+    ("br", "br/"),
+    ("frame", "frame/"),
+    ("hr", "hr/"),
+    ("bdo", "bdo[dir]"),
+    ("bdo:r", "bdo[dir=rtl]"),
+    ("bdo:l", "bdo[dir=ltr]"),
+    ("col", "col/"),
+    ("link", "link[rel=stylesheet][href]/"),
+    ("link:css", r#"link[href="${1:style}.css"]"#),
+    ("link:print", r#"link[href="${1:print}.css"][media=print]"#),
+    (
+        "link:favicon",
+        r#"link[rel="shortcut icon"][type="image/x-icon"][href="${1:favicon.ico}"]"#,
+    ),
+    (
+        "link:mf|link:manifest",
+        r#"link[rel=manifest][href="${1:manifest.json}"]"#,
+    ),
+    (
+        "link:touch",
+        r#"link[rel=apple-touch-icon][href="${1:favicon.png}"]"#,
+    ),
+    (
+        "link:rss",
+        r#"link[rel=alternate][type="application/rss+xml"][title=RSS][href="${1:rss.xml}"]"#,
+    ),
+    (
+        "link:atom",
+        r#"link[rel=alternate][type="application/atom+xml"][title=Atom][href="${1:atom.xml}"]"#,
+    ),
+    (
+        "link:im|link:import",
+        r#"link[rel=import][href="${1:component}.html"]"#,
+    ),
+    ("meta", "meta/"),
+    (
+        "meta:utf",
+        r#"meta[http-equiv=Content-Type][content="text/html;charset=UTF-8"]"#,
+    ),
+    (
+        "meta:vp",
+        r#"meta[name=viewport][content="width=${1:device-width}, initial-scale=${2:1.0}"]"#,
+    ),
+    (
+        "meta:compat",
+        r#"meta[http-equiv=X-UA-Compatible][content="${1:IE=7}"]"#,
+    ),
+    ("meta:edge", r#"meta:compat[content="${1:ie=edge}"]"#),
+    (
+        "meta:redirect",
+        r#"meta[http-equiv=refresh][content="0; url=${1:http://example.com}"]"#,
+    ),
+    (
+        "meta:refresh",
+        r#"meta[http-equiv=refresh][content="${1:5}"]"#,
+    ),
+    ("meta:kw", "meta[name=keywords][content]"),
+    ("meta:desc", "meta[name=description][content]"),
+    ("script:src", "script[src]"),
+    ("script:module", "script[type=module][src]"),
+    ("img", "img[src alt]/"),
+    ("img:s|img:srcset", "img[srcset src alt]"),
+    ("img:z|img:sizes", "img[sizes srcset src alt]"),
+    ("src|source", "source/"),
+    ("src:sc|source:src", "source[src type]"),
+    ("src:s|source:srcset", "source[srcset]"),
+    ("src:t|source:type", r#"source[srcset][type="${1:image/}"]"#),
+    ("src:z|source:sizes", "source[sizes srcset]"),
+    (
+        "src:m|source:media",
+        r#"source[media="(${1:min-width: })"][srcset]"#,
+    ),
+    (
+        "src:mt|source:media:type",
+        r#"source:media[type="${2:image/}"]"#,
+    ),
+    ("src:mz|source:media:sizes", "source:media[sizes srcset]"),
+    (
+        "src:zt|source:sizes:type",
+        r#"source[sizes srcset][type="${1:image/}"]"#,
+    ),
+    ("iframe", "iframe[src][frameborder=0]"),
+    ("embed", "embed[src type]/"),
+    ("object", "object[data type]"),
+    ("param", "param[name value]/"),
+    ("map", "map[name]"),
+    ("area", "area[shape coords href alt]/"),
+    ("area:d", "area[shape=default]"),
+    ("area:c", "area[shape=circle]"),
+    ("area:r", "area[shape=rect]"),
+    ("area:p", "area[shape=poly]"),
+    ("form", "form[action]"),
+    ("form:get", "form[method=get]"),
+    ("form:post", "form[method=post]"),
+    ("label", "label[for]"),
+    ("input", r#"input[type="${1:text}"]/"#),
+    ("inp", r#"input[name="${1}"][id="${1}"]"#),
+    ("input:h|input:hidden", "input[type=hidden][name]"),
+    ("input:t|input:text", "inp[type=text]"),
+    ("input:search", "inp[type=search]"),
+    ("input:email", "inp[type=email]"),
+    ("input:url", "inp[type=url]"),
+    ("input:p|input:password", "inp[type=password]"),
+    ("input:datetime", "inp[type=datetime]"),
+    ("input:date", "inp[type=date]"),
+    ("input:datetime-local", "inp[type=datetime-local]"),
+    ("input:month", "inp[type=month]"),
+    ("input:week", "inp[type=week]"),
+    ("input:time", "inp[type=time]"),
+    ("input:tel", "inp[type=tel]"),
+    ("input:number", "inp[type=number]"),
+    ("input:color", "inp[type=color]"),
+    ("input:c|input:checkbox", "inp[type=checkbox]"),
+    ("input:r|input:radio", "inp[type=radio]"),
+    ("input:range", "inp[type=range]"),
+    ("input:f|input:file", "inp[type=file]"),
+    ("input:s|input:submit", "input[type=submit][value]"),
+    ("input:i|input:image", "input[type=image][src alt]"),
+    (
+        "input:b|input:btn|input:button",
+        "input[type=button][value]",
+    ),
+    ("input:reset", "input[type=reset][value]"),
+    ("isindex", "isindex/"),
+    ("select", r#"select[name="${1}"][id="${1}"]"#),
+    ("select:d|select:disabled", "select[disabled.]"),
+    ("opt|option", "option[value]"),
+    (
+        "textarea",
+        r#"textarea[name="${1}"][id="${1}"][cols="${2:30}"][rows="${3:10}"]"#,
+    ),
+    ("marquee", "marquee[behavior direction]"),
+    ("menu:c|menu:context", "menu[type=context]"),
+    ("menu:t|menu:toolbar", "menu[type=toolbar]"),
+    ("video", "video[src]"),
+    ("audio", "audio[src]"),
+    ("html:xml", r#"html[xmlns="http://www.w3.org/1999/xhtml"]"#),
+    ("keygen", "keygen/"),
+    ("command", "command/"),
+    ("btn:s|button:s|button:submit", "button[type=submit]"),
+    ("btn:r|button:r|button:reset", "button[type=reset]"),
+    ("btn:b|button:b|button:button", "button[type=button]"),
+    ("btn:d|button:d|button:disabled", "button[disabled.]"),
+    (
+        "fst:d|fset:d|fieldset:d|fieldset:disabled",
+        "fieldset[disabled.]",
+    ),
+    ("bq", "blockquote"),
+    ("fig", "figure"),
+    ("figc", "figcaption"),
+    ("pic", "picture"),
+    ("ifr", "iframe"),
+    ("emb", "embed"),
+    ("obj", "object"),
+    ("cap", "caption"),
+    ("colg", "colgroup"),
+    ("fst", "fieldset"),
+    ("btn", "button"),
+    ("optg", "optgroup"),
+    ("tarea", "textarea"),
+    ("leg", "legend"),
+    ("sect", "section"),
+    ("art", "article"),
+    ("hdr", "header"),
+    ("ftr", "footer"),
+    ("adr", "address"),
+    ("dlg", "dialog"),
+    ("str", "strong"),
+    ("prog", "progress"),
+    ("mn", "main"),
+    ("tem", "template"),
+    ("fset", "fieldset"),
+    ("datal", "datalist"),
+    ("kg", "keygen"),
+    ("out", "output"),
+    ("det", "details"),
+    ("sum", "summary"),
+    ("cmd", "command"),
+    ("data", "data[value]"),
+    ("meter", "meter[value]"),
+    ("time", "time[datetime]"),
+    ("ri:d|ri:dpr", "img:s"),
+    ("ri:v|ri:viewport", "img:z"),
+    ("ri:a|ri:art", "pic>src:m+img"),
+    ("ri:t|ri:type", "pic>src:t+img"),
+    ("!!!", "{<!DOCTYPE html>}"),
+    (
+        "doc",
+        "html[lang=${lang}]>(head>meta[charset=${charset}]+meta:vp+title{${1:Document}})+body",
+    ),
+    ("!|html:5", "!!!+doc"),
+    ("c", "{<!-- ${0} -->}"),
+    ("cc:ie", "{<!--[if IE]>${0}<![endif]-->}"),
+    ("cc:noie", "{<!--[if !IE]><!-->${0}<!--<![endif]-->}"),
+    ("ol+", "ol>li"),
+    ("ul+", "ul>li"),
+    ("dl+", "dl>dt+dd"),
+    ("map+", "map>area"),
+    ("table+", "table>tr>td"),
+    ("tr+", "tr>td"),
+    ("colgroup+|colg+", "colgroup>col"),
+    ("select+", "select>option"),
+    ("optgroup+|optg+", "optgroup>option"),
+    ("pic+", "picture>source:srcset+img"),
+];
+
+/// Maps snippet names to the abbreviations they expand to.
+///
+/// When the parser meets an element whose tag name is a snippet name, the
+/// element is replaced by the parsed snippet and its own id, classes,
+/// attributes, text, children and multiplier are merged into the result.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SnippetRegistry {
+    snippets: HashMap<String, String>,
+}
+
+impl SnippetRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with the standard Emmet HTML snippets.
+    pub fn html() -> Self {
+        let mut registry = Self::new();
+        for (names, abbreviation) in HTML_SNIPPETS {
+            registry.insert(names, abbreviation);
+        }
+        registry
+    }
+
+    /// Registers a snippet under every `|`-separated alias in `names`,
+    /// replacing any existing snippet with the same name.
+    pub fn insert(&mut self, names: &str, abbreviation: &str) {
+        for name in names
+            .split('|')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            self.snippets
+                .insert(name.to_string(), abbreviation.to_string());
+        }
+    }
+
+    /// Adds every snippet of `other`, replacing snippets with the same name.
+    pub fn extend(&mut self, other: &SnippetRegistry) {
+        for (name, abbreviation) in other.iter() {
+            self.snippets
+                .insert(name.to_string(), abbreviation.to_string());
+        }
+    }
+
+    /// Removes a snippet, returning its abbreviation.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.snippets.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.snippets.get(name).map(String::as_str)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.snippets.contains_key(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.snippets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snippets.is_empty()
+    }
+
+    /// Iterates over `(name, abbreviation)` pairs in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.snippets
+            .iter()
+            .map(|(name, abbreviation)| (name.as_str(), abbreviation.as_str()))
+    }
+}
+
+/// Shared instance of the built-in HTML snippets used by default.
+pub(crate) fn builtin_html() -> &'static SnippetRegistry {
+    static HTML: OnceLock<SnippetRegistry> = OnceLock::new();
+    HTML.get_or_init(SnippetRegistry::html)
+}
@@ -0,0 +1,79 @@
+use std::ops::Range;
+
+/// A region of the parsed abbreviation, as byte offsets for slicing the
+/// input and as character offsets for editors counting characters.
+///
+/// ```
+/// use emmet_parser::parse_emmet;
+///
+/// let input = "ul>li.item*2";
+/// let li = &parse_emmet(input).unwrap()[0].children[0];
+/// let class = li.spans.classes[0];
+/// assert_eq!(&input[class.byte_range()], ".item");
+/// assert_eq!(li.spans.operator.unwrap().char_range(), 2..3);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    /// Byte offset of the first character.
+    pub start: usize,
+    /// Byte offset after the last character.
+    pub end: usize,
+    /// Character offset of the first character.
+    pub char_start: usize,
+    /// Character offset after the last character.
+    pub char_end: usize,
+}
+
+impl Span {
+    pub fn byte_range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn char_range(&self) -> Range<usize> {
+        self.char_start..self.char_end
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns the span from the start of `self` to the end of `other`.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            end: other.end,
+            char_end: other.char_end,
+            ..self
+        }
+    }
+}
+
+/// Where each part of an element was written. Part spans include their
+/// punctuation, such as the `#` of an id or the braces around text.
+///
+/// Parts that came from an expanded snippet point at the snippet's name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ElementSpans {
+    /// The tag name, or `None` for an implicit tag.
+    pub tag: Option<Span>,
+    pub id: Option<Span>,
+    /// One span per class, in the order of `classes`.
+    pub classes: Vec<Span>,
+    /// All `{...}` text of the element.
+    pub text: Option<Span>,
+    /// The `*N` multiplier.
+    pub multiplier: Option<Span>,
+    /// The `>`, `+` or `^` operator written before the element.
+    pub operator: Option<Span>,
+}
+
+/// Where the parts of an attribute were written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttributeSpans {
+    pub name: Span,
+    /// The value, including its quotes.
+    pub value: Option<Span>,
+}
@@ -0,0 +1,94 @@
+use std::borrow::Cow;
+
+use crate::{BorrowedElement, EmmetElement, EmmetParser, ErrorCode};
+
+/// Whether `value` is a slice of `input` rather than a copy.
+fn borrows_from(value: &str, input: &str) -> bool {
+    input.as_bytes().as_ptr_range().contains(&value.as_ptr())
+}
+
+#[test]
+fn test_strings_borrow_from_input() {
+    let input = "section#main.a.b[data-x=1 title=\"Hi there\"]{Text}>p.é";
+    let elements = EmmetParser::new(input).parse_borrowed().unwrap();
+    let section = &elements[0];
+
+    assert!(borrows_from(&section.tag, input));
+    assert!(borrows_from(section.id.as_ref().unwrap(), input));
+    assert!(section.classes.iter().all(|c| borrows_from(c, input)));
+    for attr in &section.attributes {
+        assert!(borrows_from(&attr.name, input));
+        assert!(borrows_from(attr.value.as_ref().unwrap(), input));
+    }
+    assert!(borrows_from(section.text.as_ref().unwrap(), input));
+    assert!(borrows_from(&section.children[0].classes[0], input));
+}
+
+#[test]
+fn test_changed_strings_are_owned() {
+    let input = "p[title=\"say \\\"hi\\\"\" lang=${lang}]{a}{b}+ul+";
+    let elements = EmmetParser::new(input).parse_borrowed().unwrap();
+
+    let p = &elements[0];
+    assert!(matches!(&p.attributes[0].value, Some(Cow::Owned(v)) if v == "say \"hi\""));
+    assert!(matches!(&p.attributes[1].value, Some(Cow::Owned(v)) if v == "en"));
+    assert!(matches!(&p.text, Some(Cow::Owned(t)) if t == "ab"));
+    assert_eq!(elements[1].tag, "ul");
+}
+
+#[test]
+fn test_snippet_strings_borrow_from_registry() {
+    let elements = EmmetParser::new("a:link").parse_borrowed().unwrap();
+    assert!(matches!(elements[0].tag, Cow::Borrowed("a")));
+    assert!(matches!(
+        elements[0].attributes[0].value,
+        Some(Cow::Borrowed("http://${0}"))
+    ));
+}
+
+#[test]
+fn test_into_owned_matches_parse() {
+    for input in [
+        "ul>li.item$*3>a[href=x]{Item $}",
+        "(header>nav)+main#content^footer",
+        "!",
+        "table>.row*2>.cell{${1:x}}",
+        "input:email[required.]/",
+        "p{Привет}|e",
+    ] {
+        let borrowed: Vec<EmmetElement> = EmmetParser::new(input)
+            .parse_borrowed()
+            .unwrap()
+            .into_iter()
+            .map(EmmetElement::from)
+            .collect();
+        assert_eq!(
+            borrowed,
+            EmmetParser::new(input).parse().unwrap(),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_partial_borrowed() {
+    let input = String::from("ul>li.item[title=");
+    let result = EmmetParser::new(&input).parse_partial_borrowed();
+    let li: &BorrowedElement = &result.elements[0].children[0];
+
+    assert!(borrows_from(&li.classes[0], &input));
+    assert_eq!(li.attributes[0].value.as_deref(), Some(""));
+    assert_eq!(
+        result.diagnostics[0].code,
+        ErrorCode::ExpectedAttributeValue
+    );
+    assert_eq!(
+        EmmetParser::new(&input).parse_partial().elements,
+        result
+            .elements
+            .into_iter()
+            .map(BorrowedElement::into_owned)
+            .collect::<Vec<_>>()
+    );
+}
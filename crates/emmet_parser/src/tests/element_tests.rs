@@ -0,0 +1,118 @@
+use crate::{
+    emmet_to_html, ElementInfo, ElementTable, EmmetParser, FormatOptions, Profile, Renderer,
+};
+
+fn render_literal(input: &str, elements: &ElementTable) -> String {
+    let mut parser = EmmetParser::new(input)
+        .without_snippets()
+        .with_elements(elements);
+    Renderer::new()
+        .with_elements(elements)
+        .render(&parser.parse().unwrap())
+}
+
+#[test]
+fn test_void_elements() {
+    assert_eq!(
+        emmet_to_html("video>source*2").unwrap(),
+        "<video src=\"\"><source /><source /></video>"
+    );
+
+    // Void without relying on snippets
+    let html = ElementTable::html();
+    assert_eq!(
+        render_literal("p>wbr+track+col+embed+param+area+base", &html),
+        "<p><wbr /><track /><col /><embed /><param /><area /><base /></p>"
+    );
+    assert_eq!(render_literal("BR", &html), "<BR />");
+    assert_eq!(render_literal("p", &html), "<p></p>");
+}
+
+#[test]
+fn test_html_table() {
+    let table = ElementTable::html();
+
+    assert!(table.is_void("source"));
+    assert!(table.is_void("WBR"));
+    assert!(!table.is_void("div"));
+    assert!(table.is_inline("strong"));
+    assert!(!table.is_inline("p"));
+    assert_eq!(table.implicit_tag(Some("ol")), "li");
+    assert_eq!(table.implicit_tag(Some("TR")), "td");
+    assert_eq!(table.implicit_tag(Some("b")), "span");
+    assert_eq!(table.implicit_tag(Some("section")), "div");
+    assert_eq!(table.implicit_tag(None), "div");
+    assert!(table.get("unknown-element").is_none());
+}
+
+#[test]
+fn test_custom_elements() {
+    let mut table = ElementTable::html();
+    table.insert(
+        "x-menu",
+        ElementInfo::new().with_implicit_child("x-menu-item"),
+    );
+    table.insert("x-icon", ElementInfo::new().void().inline());
+    table.insert(
+        "x-button",
+        ElementInfo::new()
+            .inline()
+            .with_default_attribute("type", "button")
+            .with_default_attribute("label", ""),
+    );
+
+    assert_eq!(
+        render_literal("x-menu>.a+x-icon", &table),
+        "<x-menu><x-menu-item class=\"a\"></x-menu-item><x-icon /></x-menu>"
+    );
+    assert_eq!(
+        render_literal("x-button+x-button[type=submit label=Go]", &table),
+        "<x-button type=\"button\" label=\"\"></x-button>\
+         <x-button type=\"submit\" label=\"Go\"></x-button>"
+    );
+
+    // Implicit children of inline custom elements are spans
+    assert_eq!(
+        render_literal("x-button>.label", &table),
+        "<x-button type=\"button\" label=\"\"><span class=\"label\"></span></x-button>"
+    );
+}
+
+#[test]
+fn test_replacing_and_removing_elements() {
+    let mut table = ElementTable::html();
+    table.insert("ul", ElementInfo::new());
+    assert_eq!(table.implicit_tag(Some("ul")), "div");
+
+    assert_eq!(table.remove("img").map(|info| info.void), Some(true));
+    assert_eq!(render_literal("img", &table), "<img></img>");
+}
+
+#[test]
+fn test_formatter_uses_table() {
+    let mut table = ElementTable::html();
+    table.insert("x-tag", ElementInfo::new().inline());
+    table.insert("em", ElementInfo::new());
+
+    let mut parser = EmmetParser::new("p>x-tag+em").with_elements(&table);
+    let elements = parser.parse().unwrap();
+    let format = FormatOptions::default();
+    assert_eq!(
+        Renderer::new()
+            .with_elements(&table)
+            .with_format(&format)
+            .render(&elements),
+        "<p>\n\t<x-tag></x-tag>\n\t<em></em>\n</p>"
+    );
+}
+
+#[test]
+fn test_xml_ignores_void_elements() {
+    let mut parser = EmmetParser::new("source{x}").without_snippets();
+    assert_eq!(
+        Renderer::new()
+            .with_profile(Profile::xml())
+            .render(&parser.parse().unwrap()),
+        "<source>x</source>"
+    );
+}
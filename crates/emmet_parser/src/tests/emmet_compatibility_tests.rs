@@ -1,11 +1,14 @@
-use crate::emmet_to_html;
+use crate::{emmet_to_html, parse_emmet_with_config, ExpandConfig};
 
 #[test]
 fn test_basic_markup() {
     // Basic multiplication with numbering
     let result = emmet_to_html("input[value=\"text$\"]*2");
-    // Note: Our parser doesn't support $ numbering yet, so this will be basic multiplication
     assert!(result.is_ok());
+    assert_eq!(
+        result.unwrap(),
+        "<input type=\"text\" value=\"text1\" /><input type=\"text\" value=\"text2\" />"
+    );
 
     // Basic nested elements with multiplication
     let result = emmet_to_html("ul>li.item*2");
@@ -76,12 +79,48 @@ fn test_expressions() {
 
 #[test]
 fn test_numbering() {
-    // Basic multiplication (our parser doesn't support $ numbering yet)
-    let result = emmet_to_html("ul>li.item*5");
+    let result = emmet_to_html("ul>li.item$*5");
     assert!(result.is_ok());
     let html = result.unwrap();
     assert!(html.contains("<ul>"));
-    assert!(html.contains("<li class=\"item\"></li>"));
+    assert!(html.contains("<li class=\"item1\"></li>"));
+    assert!(html.contains("<li class=\"item5\"></li>"));
+
+    let cases = [
+        ("span.item$$$*2", "<span class=\"item001\"></span><span class=\"item002\"></span>"),
+        ("span.item$@-*3", "<span class=\"item3\"></span><span class=\"item2\"></span><span class=\"item1\"></span>"),
+        ("span.item$@3*2", "<span class=\"item3\"></span><span class=\"item4\"></span>"),
+        ("span.item$@-3*2", "<span class=\"item4\"></span><span class=\"item3\"></span>"),
+        ("span.item$$@-5*2", "<span class=\"item06\"></span><span class=\"item05\"></span>"),
+        // Numbers past the largest start are written out, not wrapped
+        ("p.a$@4294967295*2", "<p class=\"a4294967295\"></p><p class=\"a4294967296\"></p>"),
+        ("h$*3", "<h1></h1><h2></h2><h3></h3>"),
+        ("ul>li*2>span{Item $}", "<ul><li><span>Item 1</span></li><li><span>Item 2</span></li></ul>"),
+        ("(dt{$}+dd{$})*2", "<dt>1</dt><dd>1</dd><dt>2</dt><dd>2</dd>"),
+        ("div#section-$*2", "<div id=\"section-1\"></div><div id=\"section-2\"></div>"),
+        ("p[data-index=$ title=\"No. $\"]*2", "<p data-index=\"1\" title=\"No. 1\"></p><p data-index=\"2\" title=\"No. 2\"></p>"),
+        ("span{\\$$}*2", "<span>$1</span><span>$2</span>"),
+        ("span.item$", "<span class=\"item$\"></span>"),
+    ];
+
+    for (abbreviation, expected) in cases {
+        assert_eq!(
+            emmet_to_html(abbreviation).unwrap(),
+            expected,
+            "abbreviation: {}",
+            abbreviation
+        );
+    }
+}
+
+#[test]
+fn test_nested_numbering_uses_nearest_multiplier() {
+    let html = emmet_to_html("ul*2>li.list$*2>span{$}").unwrap();
+    assert_eq!(
+        html,
+        "<ul><li class=\"list1\"><span>1</span></li><li class=\"list2\"><span>2</span></li></ul>"
+            .repeat(2)
+    );
 }
 
 #[test]
@@ -114,13 +153,18 @@ fn test_custom_profile() {
 
 #[test]
 fn test_custom_variables() {
-    // Basic variable substitution (our parser doesn't support variables yet)
-    // This test documents what we would need to implement
+    // Literal values are kept as written
     let result = emmet_to_html("div[charset=utf-8]{utf-8}");
     assert!(result.is_ok());
     let html = result.unwrap();
     assert!(html.contains("charset=\"utf-8\""));
     assert!(html.contains("utf-8"));
+
+    // `${name}` references the configured variables
+    let config = ExpandConfig::new().with_variable("charset", "utf-16");
+    let result = parse_emmet_with_config("meta[charset=${charset}]+p{${lang}}", &config);
+    let html: String = result.unwrap().iter().map(|e| e.to_html()).collect();
+    assert_eq!(html, "<meta charset=\"utf-16\" /><p>en</p>");
 }
 
 #[test]
@@ -213,7 +257,7 @@ fn test_complex_nested_structures() {
     assert!(html.contains("<nav>"));
     assert!(html.contains("<ul>"));
     assert!(html.contains("<li>"));
-    assert!(html.contains("<a>Link</a>"));
+    assert!(html.contains("<a href=\"\">Link</a>"));
 }
 
 #[test]
@@ -222,7 +266,7 @@ fn test_form_structures() {
     let result = emmet_to_html("form#login>input[type=email][placeholder=Email]+input[type=password][placeholder=Password]+button[type=submit]{Login}");
     assert!(result.is_ok());
     let html = result.unwrap();
-    assert!(html.contains("<form id=\"login\">"));
+    assert!(html.contains("<form id=\"login\" action=\"\">"));
     assert!(html.contains("<input type=\"email\" placeholder=\"Email\" />"));
     assert!(html.contains("<input type=\"password\" placeholder=\"Password\" />"));
     assert!(html.contains("<button type=\"submit\">Login</button>"));
@@ -231,17 +275,16 @@ fn test_form_structures() {
 #[test]
 fn test_table_structures() {
     // Table structure
-    let result = emmet_to_html("table>thead>tr>th*3{Header}+tbody>tr*2>td*3{Cell}");
+    let result = emmet_to_html("table>thead>tr>th*3{Header}^^tbody>tr*2>td*3{Cell}");
     assert!(result.is_ok());
     let html = result.unwrap();
     assert!(html.contains("<table>"));
     assert!(html.contains("<thead>"));
     assert!(html.contains("<tr>"));
-    assert!(html.contains("<th></th>"));
-    assert!(html.contains("<div>Header</div>"));
+    assert!(html.contains("<th>Header</th>"));
+    assert!(html.contains("</thead><tbody>"));
     assert!(html.contains("<tbody>"));
-    assert!(html.contains("<td></td>"));
-    assert!(html.contains("<div>Cell</div>"));
+    assert!(html.contains("<td>Cell</td>"));
 }
 
 #[test]
@@ -357,7 +400,7 @@ fn test_nested_multiplication() {
     assert!(html.contains("<div>"));
     assert!(html.contains("<ul>"));
     let li_count = html.matches("<li>").count();
-    let a_count = html.matches("<a>").count();
+    let a_count = html.matches("<a href=\"\">").count();
     assert_eq!(li_count, 2);
     assert_eq!(a_count, 6); // 2 li * 3 a each
 }
@@ -375,3 +418,44 @@ fn test_sibling_multiplication() {
     assert_eq!(p_count, 3);
     assert_eq!(span_count, 1);
 }
+
+#[test]
+fn test_operators() {
+    // Operator examples from the upstream Emmet abbreviation test suite
+    let cases = [
+        ("p>em", "<p><em></em></p>"),
+        ("p+em", "<p></p><em></em>"),
+        ("p+em>span", "<p></p><em><span></span></em>"),
+        ("p>em+span", "<p><em></em><span></span></p>"),
+        ("ul>li+li", "<ul><li></li><li></li></ul>"),
+        ("p>em>span+b", "<p><em><span></span><b></b></em></p>"),
+        ("p>em>span^b", "<p><em><span></span></em><b></b></p>"),
+        ("p>em>span^^b", "<p><em><span></span></em></p><b></b>"),
+        ("p>em>span^^^^b", "<p><em><span></span></em></p><b></b>"),
+        ("div>p*2+span", "<div><p></p><p></p><span></span></div>"),
+        (
+            "div>(header>nav)+main+(footer>p)",
+            "<div><header><nav></nav></header><main></main><footer><p></p></footer></div>",
+        ),
+        (
+            "div>p{one}+p{two}^section",
+            "<div><p>one</p><p>two</p></div><section></section>",
+        ),
+        ("ul>li*2{item}", "<ul><li>item</li><li>item</li></ul>"),
+    ];
+
+    for (abbreviation, expected) in cases {
+        assert_eq!(
+            emmet_to_html(abbreviation).unwrap(),
+            expected,
+            "abbreviation: {}",
+            abbreviation
+        );
+    }
+}
+
+#[test]
+fn test_missing_operator_is_error() {
+    assert!(emmet_to_html("div{a}@").is_err());
+    assert!(emmet_to_html("(p)(em)").is_err());
+}
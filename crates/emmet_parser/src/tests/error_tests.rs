@@ -0,0 +1,125 @@
+use crate::{
+    emmet_to_html, EmmetError, EmmetParser, ErrorCode, Expected, ParseError, SnippetRegistry,
+};
+
+fn parse_error(input: &str) -> ParseError {
+    emmet_to_html(input)
+        .unwrap_err()
+        .parse_error()
+        .cloned()
+        .unwrap()
+}
+
+#[test]
+fn test_error_codes_and_spans() {
+    let cases = [
+        ("div>@", ErrorCode::ExpectedElement, 4..5),
+        ("(p)(em)", ErrorCode::UnexpectedCharacter, 3..4),
+        ("div)", ErrorCode::UnmatchedParen, 3..4),
+        ("p+(div>p", ErrorCode::UnclosedGroup, 2..3),
+        ("(p)>em", ErrorCode::GroupChildren, 3..4),
+        ("div#", ErrorCode::ExpectedName, 4..4),
+        ("div.", ErrorCode::ExpectedClassName, 4..4),
+        ("div*abc", ErrorCode::InvalidNumber, 4..5),
+        ("div*99999999999", ErrorCode::InvalidNumber, 4..5),
+        ("p.a$@99999999999", ErrorCode::InvalidNumber, 5..6),
+        ("p[x=\"$@-4294967296\"]", ErrorCode::InvalidNumber, 8..9),
+        ("p{é $$@99999999999}*2", ErrorCode::InvalidNumber, 8..9),
+        ("div{Hello", ErrorCode::UnclosedText, 3..4),
+        ("a[title='x]", ErrorCode::UnclosedQuote, 8..9),
+        ("a[title=]", ErrorCode::ExpectedAttributeValue, 8..9),
+        ("div|", ErrorCode::ExpectedFilter, 4..4),
+        ("p*2*3", ErrorCode::RepeatedMultiplier, 3..4),
+    ];
+
+    for (input, code, span) in cases {
+        let err = parse_error(input);
+        assert_eq!((input, err.code), (input, code));
+        assert_eq!((input, err.span.byte_range()), (input, span));
+    }
+}
+
+#[test]
+fn test_expected_and_found() {
+    let err = parse_error("div[=value]");
+    assert_eq!(err.expected, [Expected::AttributeName, Expected::Char(']')]);
+    assert_eq!(err.found, Some('='));
+    assert_eq!(err.message, "expected attribute name or ']', found '='");
+
+    let err = parse_error("(div>p");
+    assert_eq!(err.expected, [Expected::Char(')')]);
+    assert_eq!(err.found, None);
+    assert_eq!(err.message, "expected ')', found end of input");
+
+    let err = parse_error("p*٣");
+    assert_eq!(err.code, ErrorCode::InvalidNumber);
+    assert_eq!(err.message, "expected number, found '٣'");
+
+    let err = parse_error("p{a}@");
+    assert_eq!(err.message, "expected '+', '>' or '^', found '@'");
+}
+
+#[test]
+fn test_error_variants_and_display() {
+    let err = emmet_to_html("a[title=]").unwrap_err();
+    assert!(matches!(err, EmmetError::InvalidAttribute(_)));
+    assert_eq!(
+        err.to_string(),
+        "Invalid attribute syntax: expected attribute value, found ']' at column 9"
+    );
+
+    let err = emmet_to_html("div{Hello").unwrap_err();
+    assert!(matches!(err, EmmetError::UnclosedBracket(_)));
+    assert_eq!(
+        err.to_string(),
+        "Unclosed bracket: expected '}', found end of input at column 4"
+    );
+    assert_eq!(err.parse_error().unwrap().code.as_str(), "E009");
+}
+
+#[test]
+fn test_render() {
+    let err = emmet_to_html("ul>li*x").unwrap_err();
+    assert_eq!(
+        err.render("ul>li*x"),
+        "error[E008]: expected number, found 'x'\n  |\n1 | ul>li*x\n  |       ^"
+    );
+
+    // At the end of input the caret points past the last character
+    let err = parse_error("div.");
+    assert_eq!(
+        err.render("div."),
+        "error[E007]: expected class name, found end of input\n  |\n1 | div.\n  |     ^"
+    );
+
+    // Only the line holding the error is shown
+    let input = "ul>\nli*x";
+    let err = EmmetParser::new(input).parse().unwrap_err();
+    assert_eq!(
+        err.render(input),
+        "error[E008]: expected number, found 'x'\n  |\n2 | li*x\n  |    ^"
+    );
+}
+
+#[test]
+fn test_snippet_errors_point_at_snippet_name() {
+    let mut snippets = SnippetRegistry::new();
+    snippets.insert("bad", "div[");
+
+    let err = EmmetParser::new("p+bad")
+        .with_snippets(&snippets)
+        .parse()
+        .unwrap_err();
+    let parse_error = err.parse_error().unwrap();
+    assert_eq!(parse_error.code, ErrorCode::InvalidSnippet);
+    assert_eq!(parse_error.span.byte_range(), 2..5);
+    assert_eq!(
+        err.render("p+bad"),
+        "error[E013]: in snippet 'bad': Invalid syntax: expected attribute name or ']', \
+         found end of input at column 5\n  |\n1 | p+bad\n  |   ^^^"
+    );
+
+    // Other errors render without a snippet of the input
+    let err = EmmetError::UnknownVariable("x".to_string());
+    assert_eq!(err.render("p{${x}}"), "error: Unknown variable 'x'");
+}
@@ -0,0 +1,121 @@
+use crate::{emmet_to_html, emmet_to_snippet, EmmetParser, LspFields, Profile, Quote, Renderer};
+
+fn render(profile: Profile, input: &str) -> String {
+    let mut parser = EmmetParser::new(input).without_snippets();
+    Renderer::new()
+        .with_profile(profile)
+        .render(&parser.parse().unwrap())
+}
+
+#[test]
+fn test_attribute_values_are_escaped() {
+    assert_eq!(
+        emmet_to_html(r#"a[title='say "hi"' href="?a=1&b=2"]"#).unwrap(),
+        "<a href=\"?a=1&amp;b=2\" title=\"say &quot;hi&quot;\"></a>"
+    );
+    assert_eq!(
+        emmet_to_html(r#"div[data-x="a < b"]"#).unwrap(),
+        "<div data-x=\"a &lt; b\"></div>"
+    );
+    // Backslash-escaped quotes inside quoted values
+    assert_eq!(
+        emmet_to_html(r#"a[title="say \"hi\""]"#).unwrap(),
+        "<a href=\"\" title=\"say &quot;hi&quot;\"></a>"
+    );
+}
+
+#[test]
+fn test_attribute_escaping_follows_quote_style() {
+    let single = Profile::html().with_quote(Quote::Single);
+    assert_eq!(
+        render(single, r#"p[title="it's \"ok\""]"#),
+        "<p title='it&#39;s \"ok\"'></p>"
+    );
+    assert_eq!(
+        render(Profile::html(), r#"p[id="a&b" class="x<y" title="it's"]"#),
+        "<p id=\"a&amp;b\" class=\"x&lt;y\" title=\"it's\"></p>"
+    );
+}
+
+#[test]
+fn test_entities_are_not_escaped_twice() {
+    assert_eq!(
+        emmet_to_html(r#"p[title="&copy; 2024 &#169; &#xA9; & &x;y &#; &#xG;"]"#).unwrap(),
+        "<p title=\"&copy; 2024 &#169; &#xA9; &amp; &x;y &amp;#; &amp;#xG;\"></p>"
+    );
+    assert_eq!(
+        emmet_to_html("p{&copy; a < b & c}|e").unwrap(),
+        "<p>&copy; a &lt; b &amp; c</p>"
+    );
+}
+
+#[test]
+fn test_text_is_escaped_on_request() {
+    // Text is markup unless escaping is asked for
+    assert_eq!(
+        emmet_to_html("div{a < b & c}").unwrap(),
+        "<div>a < b & c</div>"
+    );
+    assert_eq!(
+        emmet_to_html("div{a < b & c}+{<br>}|e").unwrap(),
+        "<div>a &lt; b &amp; c</div>&lt;br&gt;"
+    );
+
+    let elements = EmmetParser::new("p{x > y}").parse().unwrap();
+    assert_eq!(
+        Renderer::new().escape_text(true).render(&elements),
+        "<p>x &gt; y</p>"
+    );
+}
+
+#[test]
+fn test_generated_markup_is_not_escaped() {
+    let html = emmet_to_html("!|e").unwrap();
+    assert!(html.starts_with("<!DOCTYPE html><html lang=\"en\">"));
+    assert_eq!(html, emmet_to_html("!").unwrap());
+    assert_eq!(
+        emmet_to_html("!!!+c+{a<b}|e").unwrap(),
+        "<!DOCTYPE html><!--  -->a&lt;b"
+    );
+}
+
+#[test]
+fn test_written_markup_is_escaped() {
+    assert_eq!(
+        emmet_to_html("p{<!-- hi -->}|e").unwrap(),
+        "<p>&lt;!-- hi --&gt;</p>"
+    );
+    assert_eq!(
+        emmet_to_html("{<!DOCTYPE x>}|e").unwrap(),
+        "&lt;!DOCTYPE x&gt;"
+    );
+    assert_eq!(
+        emmet_to_html("p{<!DOCTYPE x>}|e").unwrap(),
+        "<p>&lt;!DOCTYPE x&gt;</p>"
+    );
+}
+
+#[test]
+fn test_filters() {
+    let mut parser = EmmetParser::new("ul>li*2|e|t");
+    parser.parse().unwrap();
+    assert_eq!(parser.filters(), ["e", "t"]);
+
+    assert!(EmmetParser::new("div|").parse().is_err());
+    assert!(EmmetParser::new("div|e>p").parse().is_err());
+    assert!(EmmetParser::new("(div|e)").parse().is_err());
+
+    // Text and values may contain `|`
+    assert_eq!(
+        emmet_to_html(r#"p[title="a|b"]{c|d}"#).unwrap(),
+        "<p title=\"a|b\">c|d</p>"
+    );
+}
+
+#[test]
+fn test_escaping_keeps_fields() {
+    assert_eq!(
+        emmet_to_snippet(r#"a[title="<${1:x}>"]{a&b}|e"#, &LspFields).unwrap(),
+        "<a href=\"${1}\" title=\"&lt;${2:x}>\">a&amp;b</a>"
+    );
+}
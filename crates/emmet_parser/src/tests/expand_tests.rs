@@ -0,0 +1,242 @@
+use crate::{
+    emmet_to_html, emmet_to_snippet, expand, expand_with_elements, parse_emmet, ElementInfo,
+    ElementTable, EmmetParser, ExpandedElement, ExpandedNode, LspFields, PlainFields, Profile,
+    Renderer, SnippetRegistry,
+};
+
+fn expand_input(input: &str) -> Vec<ExpandedNode> {
+    expand(&parse_emmet(input).unwrap())
+}
+
+fn element(node: &ExpandedNode) -> &ExpandedElement {
+    node.as_element().expect("an element")
+}
+
+fn tags(nodes: &[ExpandedNode]) -> Vec<&str> {
+    nodes
+        .iter()
+        .map(|node| element(node).tag.as_str())
+        .collect()
+}
+
+#[test]
+fn test_repetitions_are_written_out() {
+    let nodes = expand_input("ul>li*3>a");
+    let ul = element(&nodes[0]);
+    assert_eq!(tags(&ul.children), ["li", "li", "li"]);
+    for li in &ul.children {
+        assert_eq!(tags(&element(li).children), ["a"]);
+    }
+}
+
+#[test]
+fn test_groups_are_flattened() {
+    let nodes = expand_input("(dt+dd)*2+p");
+    assert_eq!(tags(&nodes), ["dt", "dd", "dt", "dd", "p"]);
+}
+
+#[test]
+fn test_numbering_is_resolved() {
+    let nodes = expand_input("h$.c$$@-*2>{Item $}+span.x$");
+    let h1 = element(&nodes[0]);
+    let h2 = element(&nodes[1]);
+    assert_eq!((h1.tag.as_str(), h2.tag.as_str()), ("h1", "h2"));
+    assert_eq!(h1.attribute("class"), Some("c02"));
+    assert_eq!(h2.attribute("class"), Some("c01"));
+    assert_eq!(h2.children[0], ExpandedNode::Text("Item 2".to_string()));
+    // Children are numbered by the nearest multiplier
+    assert_eq!(element(&h2.children[1]).attribute("class"), Some("x2"));
+
+    let nodes = expand_input("ul*2>li.a$*3");
+    let second = element(&nodes[1]);
+    assert_eq!(element(&second.children[2]).attribute("class"), Some("a3"));
+}
+
+#[test]
+fn test_attributes_in_render_order() {
+    let nodes = expand_input("div#name.a.b[type=text][required]");
+    let div = element(&nodes[0]);
+    let names: Vec<_> = div
+        .attributes
+        .iter()
+        .map(|attr| attr.name.as_str())
+        .collect();
+    assert_eq!(names, ["id", "class", "type", "required"]);
+    assert_eq!(div.attribute("class"), Some("a b"));
+    assert_eq!(div.attribute("required"), Some(""));
+    assert!(div.attributes[3].is_boolean());
+    assert_eq!(div.attribute("missing"), None);
+}
+
+#[test]
+fn test_text_and_comments() {
+    let nodes = expand_input("p{Hi}>b+{!}");
+    let p = element(&nodes[0]);
+    assert_eq!(p.children[0], ExpandedNode::Text("Hi".to_string()));
+    assert_eq!(tags(&p.children[1..2]), ["b"]);
+    assert_eq!(p.children[2], ExpandedNode::Text("!".to_string()));
+
+    assert_eq!(expand_input("c"), [ExpandedNode::Comment("  ".to_string())]);
+    // Text holding more than one comment stays text
+    assert!(matches!(expand_input("cc:noie")[0], ExpandedNode::Text(_)));
+}
+
+#[test]
+fn test_doctype() {
+    let nodes = expand_input("!");
+    assert_eq!(nodes[0], ExpandedNode::Doctype("html".to_string()));
+    assert_eq!(tags(&nodes[1..]), ["html"]);
+
+    let mut snippets = SnippetRegistry::new();
+    snippets.insert("dt", "{<!doctype html PUBLIC \"x\">}");
+    snippets.insert("dtx", "{<!DOCTYPE html>x}");
+    let expand_snippet = |input| {
+        let elements = EmmetParser::new(input)
+            .with_snippets(&snippets)
+            .parse()
+            .unwrap();
+        expand(&elements)
+    };
+    assert_eq!(
+        expand_snippet("dt"),
+        [ExpandedNode::Doctype("html PUBLIC \"x\"".to_string())]
+    );
+    assert!(matches!(expand_snippet("dtx")[0], ExpandedNode::Text(_)));
+}
+
+#[test]
+fn test_markup_is_only_read_from_snippets() {
+    for input in ["{<!DOCTYPE html>}", "{<!-- x -->}", "p{<!-- x -->}"] {
+        let nodes = expand_input(input);
+        assert!(
+            !nodes
+                .iter()
+                .any(|node| matches!(node, ExpandedNode::Comment(_) | ExpandedNode::Doctype(_))),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_snippets_are_substituted() {
+    let nodes = expand_input("a:link");
+    let a = element(&nodes[0]);
+    assert_eq!(a.tag, "a");
+    assert_eq!(a.attribute("href"), Some("http://"));
+}
+
+#[test]
+fn test_fields_are_resolved() {
+    // Placeholders by default
+    let nodes = expand_input("input+!");
+    assert_eq!(element(&nodes[0]).attribute("type"), Some("text"));
+    let html = Renderer::new().render_nodes(&nodes);
+    assert!(html.contains("<title>Document</title>"));
+    assert!(!html.contains('$'));
+
+    // Written and numbered by the renderer's field formatter
+    let elements = parse_emmet("input+a:link+p>{Hi ${1:you}}+c").unwrap();
+    let nodes = Renderer::new().with_fields(&LspFields).expand(&elements);
+    assert_eq!(element(&nodes[0]).attribute("type"), Some("${1:text}"));
+    let a = element(&nodes[1]);
+    assert_eq!(a.attribute("href"), Some("http://${2}"));
+    assert_eq!(a.children, [ExpandedNode::Text("${3}".to_string())]);
+    let p = element(&nodes[2]);
+    assert_eq!(p.children[0], ExpandedNode::Text("Hi ${4:you}".to_string()));
+    assert_eq!(p.children[1], ExpandedNode::Comment(" ${5} ".to_string()));
+
+    // Elements closed without content have no field
+    let cursor = PlainFields::new("|");
+    let renderer = Renderer::new()
+        .with_fields(&cursor)
+        .with_profile(Profile::xml());
+    let nodes = renderer.expand(&parse_emmet("div+p{x}").unwrap());
+    assert!(element(&nodes[0]).children.is_empty());
+    assert_eq!(
+        renderer.render_nodes(&nodes),
+        emmet_to_html("div+p{x}")
+            .unwrap()
+            .replace("<div></div>", "<div/>")
+    );
+
+    // Rendering the expanded nodes gives the snippet
+    for input in ["input+a:link+p>{Hi ${1:you}}+c", "!", "ul>li*2>a"] {
+        let elements = parse_emmet(input).unwrap();
+        let renderer = Renderer::new().with_fields(&LspFields);
+        assert_eq!(
+            renderer.render_nodes(&renderer.expand(&elements)),
+            emmet_to_snippet(input, &LspFields).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_default_attributes() {
+    let mut table = ElementTable::html();
+    table.insert(
+        "icon",
+        ElementInfo::new().void().with_default_attribute("alt", ""),
+    );
+    let elements = parse_emmet("icon*2+icon[alt=x]").unwrap();
+
+    let nodes = expand_with_elements(&elements, &table);
+    assert_eq!(element(&nodes[1]).attribute("alt"), Some(""));
+    assert_eq!(element(&nodes[2]).attributes.len(), 1);
+    assert!(expand(&elements)[0]
+        .as_element()
+        .unwrap()
+        .attributes
+        .is_empty());
+}
+
+#[test]
+fn test_self_closing_and_spans() {
+    let nodes = expand_input("div>y+x/");
+    let div = element(&nodes[0]);
+    assert!(!element(&div.children[0]).self_closing);
+    assert!(element(&div.children[1]).self_closing);
+    assert_eq!(element(&div.children[1]).span.byte_range(), 6..8);
+}
+
+#[test]
+fn test_renderers_consume_expanded_nodes() {
+    for input in ["ul>li.item$*3>a:link", "!", "table>tr*2>td{$}*2", "c+p"] {
+        let elements = parse_emmet(input).unwrap();
+        let renderer = Renderer::new();
+        assert_eq!(
+            renderer.render_nodes(&expand(&elements)),
+            emmet_to_html(input).unwrap()
+        );
+        assert_eq!(
+            renderer.render_nodes(&elements[0].expand()),
+            elements[0].to_html()
+        );
+    }
+}
+
+#[test]
+fn test_escaping_leaves_comments() {
+    assert_eq!(emmet_to_html("c+{<b>}|e").unwrap(), "<!--  -->&lt;b&gt;");
+    assert_eq!(emmet_to_html("c{<b>}|e").unwrap(), "<!-- <b> -->");
+}
+
+#[test]
+fn test_comments_cannot_be_ended_early() {
+    assert_eq!(emmet_to_html("c{a-->b}").unwrap(), "<!-- a--&gt;b -->");
+    assert_eq!(emmet_to_html("c{a--!>b}").unwrap(), "<!-- a--!&gt;b -->");
+
+    let mut snippets = SnippetRegistry::new();
+    snippets.insert("tight", "{<!--${0}-->}");
+    for (input, expected) in [
+        ("tight{>a}", "<!--&gt;a-->"),
+        ("tight{->a}", "<!---&gt;a-->"),
+        ("tight{a->}", "<!--a->-->"),
+    ] {
+        let elements = EmmetParser::new(input)
+            .with_snippets(&snippets)
+            .parse()
+            .unwrap();
+        assert_eq!(Renderer::new().render(&elements), expected, "{}", input);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{
+    emmet_to_html, emmet_to_snippet, parse_emmet, FieldFormatter, LspFields, PlainFields,
+    TextMateFields,
+};
+
+#[test]
+fn test_lsp_fields() {
+    let cases = [
+        ("a", "<a href=\"${1}\">${2}</a>"),
+        ("ul>li*2", "<ul><li>${1}</li><li>${2}</li></ul>"),
+        ("p{Hello}", "<p>Hello</p>"),
+        ("br", "<br />"),
+        ("c", "<!-- ${1} -->"),
+        ("a:link{Home}", "<a href=\"http://${1}\">Home</a>"),
+        (
+            "textarea",
+            "<textarea name=\"${1}\" id=\"${1}\" cols=\"${2:30}\" rows=\"${3:10}\">${4}</textarea>",
+        ),
+    ];
+
+    for (abbreviation, expected) in cases {
+        assert_eq!(
+            emmet_to_snippet(abbreviation, &LspFields).unwrap(),
+            expected,
+            "abbreviation: {}",
+            abbreviation
+        );
+    }
+}
+
+#[test]
+fn test_fields_numbered_across_elements() {
+    assert_eq!(
+        emmet_to_snippet("label+input:text", &LspFields).unwrap(),
+        "<label for=\"${1}\">${2}</label><input type=\"text\" name=\"${3}\" id=\"${3}\" />"
+    );
+    // Fields of nested snippets stay apart from the element's own
+    assert_eq!(
+        emmet_to_snippet("inp", &LspFields).unwrap(),
+        "<input type=\"${1:text}\" name=\"${2}\" id=\"${2}\" />"
+    );
+
+    let html5 = emmet_to_snippet("!", &LspFields).unwrap();
+    assert!(html5.contains("content=\"width=${1:device-width}, initial-scale=${2:1.0}\""));
+    assert!(html5.contains("<title>${3:Document}</title>"));
+    assert!(html5.ends_with("<body>${4}</body></html>"));
+}
+
+#[test]
+fn test_textmate_fields() {
+    assert_eq!(
+        emmet_to_snippet("a+select", &TextMateFields).unwrap(),
+        "<a href=\"$1\">$2</a><select name=\"$3\" id=\"$3\">$4</select>"
+    );
+    assert_eq!(
+        emmet_to_snippet("p{run `ls`}", &TextMateFields).unwrap(),
+        "<p>run \\`ls\\`</p>"
+    );
+}
+
+#[test]
+fn test_plain_fields() {
+    let formatter = PlainFields::new("|");
+    assert_eq!(
+        emmet_to_snippet("a+link:css", &formatter).unwrap(),
+        "<a href=\"|\">|</a><link rel=\"stylesheet\" href=\"style.css\" />"
+    );
+    assert_eq!(
+        emmet_to_snippet("!", &PlainFields::default()).unwrap(),
+        emmet_to_html("!").unwrap()
+    );
+}
+
+#[test]
+fn test_literal_text_is_escaped() {
+    assert_eq!(
+        emmet_to_snippet("p{${name} costs {x}}", &LspFields).unwrap(),
+        "<p>\\${name\\} costs {x\\}</p>"
+    );
+    // An escaped field is literal text
+    assert_eq!(
+        emmet_to_snippet("p{\\${1:x} \\$}", &LspFields).unwrap(),
+        "<p>\\${1:x\\} \\$</p>"
+    );
+}
+
+#[test]
+fn test_custom_formatter() {
+    struct Markers;
+
+    impl FieldFormatter for Markers {
+        fn field(&self, index: usize, placeholder: &str) -> String {
+            format!("[{}:{}]", index, placeholder)
+        }
+    }
+
+    let result = parse_emmet("span[title=${1:Title}]").unwrap();
+    assert_eq!(
+        result[0].to_snippet(&Markers),
+        "<span title=\"[1:Title]\">[2:]</span>"
+    );
+}
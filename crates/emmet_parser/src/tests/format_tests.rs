@@ -0,0 +1,122 @@
+use crate::{emmet_to_html, parse_emmet, FormatOptions, LspFields, Renderer};
+
+fn format_with(format: &FormatOptions, input: &str) -> String {
+    let elements = parse_emmet(input).unwrap();
+    Renderer::new().with_format(format).render(&elements)
+}
+
+fn format(input: &str) -> String {
+    format_with(&FormatOptions::default(), input)
+}
+
+#[test]
+fn test_block_elements_are_indented() {
+    assert_eq!(format("ul>li*2"), "<ul>\n\t<li></li>\n\t<li></li>\n</ul>");
+    assert_eq!(
+        format("table+"),
+        "<table>\n\t<tr>\n\t\t<td></td>\n\t</tr>\n</table>"
+    );
+    assert_eq!(
+        format("(header>nav)+main"),
+        "<header>\n\t<nav></nav>\n</header>\n<main></main>"
+    );
+    assert_eq!(format("div{Hi}>p"), "<div>\n\tHi\n\t<p></p>\n</div>");
+}
+
+#[test]
+fn test_html5_document() {
+    let expected = "<!DOCTYPE html>\n\
+        <html lang=\"en\">\n\
+        \t<head>\n\
+        \t\t<meta charset=\"UTF-8\" />\n\
+        \t\t<meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\" />\n\
+        \t\t<title>Document</title>\n\
+        \t</head>\n\
+        \t<body>\n\
+        \t\t\n\
+        \t</body>\n\
+        </html>";
+    assert_eq!(format("!"), expected);
+}
+
+#[test]
+fn test_inline_elements_stay_inline() {
+    assert_eq!(format("p>a+em"), "<p><a href=\"\"></a><em></em></p>");
+    assert_eq!(format("p{Hi }>a"), "<p>Hi <a href=\"\"></a></p>");
+    assert_eq!(format("a+strong"), "<a href=\"\"></a><strong></strong>");
+    assert_eq!(
+        format("div>a+b+p"),
+        "<div>\n\t<a href=\"\"></a><b></b>\n\t<p></p>\n</div>"
+    );
+    assert_eq!(format("span+div"), "<span></span>\n<div></div>");
+    // An inline element holding a block goes on its own line
+    assert_eq!(format("a>div"), "<a href=\"\">\n\t<div></div>\n</a>");
+}
+
+#[test]
+fn test_inline_break() {
+    assert_eq!(
+        format("p>em*3"),
+        "<p>\n\t<em></em>\n\t<em></em>\n\t<em></em>\n</p>"
+    );
+
+    let format = FormatOptions {
+        inline_break: 0,
+        ..FormatOptions::default()
+    };
+    assert_eq!(
+        format_with(&format, "p>em*3"),
+        "<p><em></em><em></em><em></em></p>"
+    );
+}
+
+#[test]
+fn test_indent_newline_and_base_indent() {
+    let format = FormatOptions {
+        indent: "  ".to_string(),
+        newline: "\r\n".to_string(),
+        base_indent: "    ".to_string(),
+        ..FormatOptions::default()
+    };
+    assert_eq!(
+        format_with(&format, "ul>li"),
+        "<ul>\r\n      <li></li>\r\n    </ul>"
+    );
+}
+
+#[test]
+fn test_element_lists() {
+    let format = FormatOptions {
+        inline_elements: vec!["li".to_string()],
+        force_indent: vec!["p".to_string()],
+        ..FormatOptions::default()
+    };
+    assert_eq!(
+        format_with(&format, "ul>li*2"),
+        "<ul><li></li><li></li></ul>"
+    );
+    assert_eq!(format_with(&format, "p"), "<p>\n\t\n</p>");
+    assert_eq!(format_with(&format, "body"), "<body></body>");
+}
+
+#[test]
+fn test_renderer_defaults_to_one_line() {
+    let elements = parse_emmet("!").unwrap();
+    assert_eq!(
+        Renderer::new().render(&elements),
+        emmet_to_html("!").unwrap()
+    );
+}
+
+#[test]
+fn test_formatted_snippet() {
+    let elements = parse_emmet("ul>li*2>a").unwrap();
+    let format = FormatOptions::default();
+    assert_eq!(
+        Renderer::new()
+            .with_format(&format)
+            .with_fields(&LspFields)
+            .render(&elements),
+        "<ul>\n\t<li><a href=\"${1}\">${2}</a></li>\n\t<li><a href=\"${3}\">${4}</a></li>\n</ul>"
+    );
+}
@@ -0,0 +1,317 @@
+use crate::{emmet_to_html, html_to_abbreviation, html_to_elements, ElementKind, EmmetParser};
+
+#[test]
+fn test_elements_and_attributes() {
+    assert_eq!(html_to_abbreviation("<div></div>"), "div");
+    assert_eq!(
+        html_to_abbreviation("<section id=\"main\" class=\"a  b\"></section>"),
+        "section#main.a.b"
+    );
+    assert_eq!(
+        html_to_abbreviation("<input type=text disabled data-x><br>"),
+        "input[type=\"text\" disabled data-x.]+br"
+    );
+    assert_eq!(
+        html_to_abbreviation("<a href=\"/a?b=1&amp;c=2\" title='say &quot;hi&quot;'>x</a>"),
+        "a[href=\"/a?b=1&c=2\" title='say \"hi\"']{x}"
+    );
+}
+
+#[test]
+fn test_unrepresentable_parts_are_kept_as_attributes_or_dropped() {
+    let elements = html_to_elements("<div id=\"a.b\" class=\"x@y\" @click=\"go\"></div>");
+    let names: Vec<_> = elements[0]
+        .attributes
+        .iter()
+        .map(|attr| attr.name.as_str())
+        .collect();
+    assert_eq!(names, ["id", "class"]);
+    assert_eq!(elements[0].id, None);
+}
+
+#[test]
+fn test_text_and_whitespace() {
+    assert_eq!(
+        html_to_abbreviation("<p>  Hello\n   world </p>"),
+        "p{Hello world}"
+    );
+    assert_eq!(
+        html_to_abbreviation("<p>Hello <b>world</b>!</p>"),
+        "p>{Hello }+b{world}+{!}"
+    );
+    assert_eq!(
+        html_to_abbreviation("<div>\n  <p>a</p>\n  <p>b</p>\n</div>"),
+        "div>p{a}+p{b}"
+    );
+    assert_eq!(html_to_abbreviation("<pre> a\n b</pre>"), "pre{ a\n b}");
+    assert_eq!(
+        html_to_abbreviation("<style>p { color: red }</style>"),
+        "style{p { color: red }}"
+    );
+    assert_eq!(html_to_abbreviation("<p>a } b</p>"), "p{a \\} b}");
+    assert_eq!(html_to_abbreviation("1 < 2"), "{1 < 2}");
+}
+
+#[test]
+fn test_non_ascii_text() {
+    assert_eq!(html_to_abbreviation("<p>é</p>"), "p{é}");
+    assert_eq!(
+        html_to_abbreviation("<ul><li>日本</li><li>日本</li></ul>"),
+        "ul>li{日本}*2"
+    );
+    assert_eq!(html_to_abbreviation("<p>ü<b>ß</b>→</p>"), "p>{ü}+b{ß}+{→}");
+    assert_eq!(html_to_abbreviation("ñ < 2"), "{ñ < 2}");
+}
+
+#[test]
+fn test_comments_and_doctype() {
+    assert_eq!(
+        html_to_abbreviation("<!-- <p> --><p>x</p><?xml?>"),
+        "{<!-- <p> -->}+p{x}"
+    );
+    // Comments are kept as written, and text is not joined onto them
+    assert_eq!(
+        html_to_abbreviation("<div><!-- a  b -->c<!-- d</div>"),
+        "div>{<!-- a  b -->}+{c}+{<!-- d</div>-->}"
+    );
+    assert_eq!(
+        html_to_abbreviation("<!DOCTYPE html>\n<html></html>"),
+        "{<!DOCTYPE html>}+html"
+    );
+
+    let elements = html_to_elements("<!doctype html><!-- a --><![CDATA[x]]>");
+    let kinds: Vec<_> = elements.iter().map(|element| element.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            ElementKind::Doctype,
+            ElementKind::Comment,
+            ElementKind::Text
+        ]
+    );
+    assert_eq!(elements[1].text.as_deref(), Some(" a "));
+    assert_eq!(
+        crate::Renderer::new().escape_text(true).render(&elements),
+        "<!DOCTYPE html><!-- a -->&lt;![CDATA[x]]&gt;"
+    );
+}
+
+#[test]
+fn test_unclosed_tags() {
+    assert_eq!(html_to_abbreviation("<div><span>a"), "div>span{a}");
+    assert_eq!(
+        html_to_abbreviation("<ul><li>a<li>b</ul><p>c<p>d"),
+        "ul>li{a}+li{b}^p{c}+p{d}"
+    );
+    assert_eq!(
+        html_to_abbreviation("<table><tr><td>a<td>b<tr><td>c</table>"),
+        "table>tr>td{a}+td{b}^tr>td{c}"
+    );
+    assert_eq!(html_to_abbreviation("<p>a<div>b</div>"), "p{a}+div{b}");
+    // End tags of elements that are not open are ignored
+    assert_eq!(html_to_abbreviation("<div>a</span></div>"), "div{a}");
+    assert_eq!(html_to_abbreviation("<div class=\"x"), ".x");
+    assert_eq!(html_to_abbreviation("<x-icon />"), "x-icon/");
+}
+
+#[test]
+fn test_repeated_siblings() {
+    assert_eq!(html_to_abbreviation("<p></p><p></p><p></p>"), "p*3");
+    assert_eq!(
+        html_to_abbreviation("<ul><li class=\"a\">x</li><li class=\"a\">x</li></ul>"),
+        "ul>.a{x}*2"
+    );
+    assert_eq!(
+        html_to_abbreviation("<p>a</p><p>a</p><p>b</p>"),
+        "p{a}*2+p{b}"
+    );
+    // Nested runs keep their own multiplier
+    assert_eq!(
+        html_to_abbreviation(
+            "<div class=\"col\"><span>x</span><span>x</span></div>\
+             <div class=\"col\"><span>x</span><span>x</span></div>"
+        ),
+        ".col*2>span{x}*2"
+    );
+}
+
+#[test]
+fn test_long_runs() {
+    let html = format!("<ul>{}</ul>", "<li class=\"x\">a</li>".repeat(20_000));
+    assert_eq!(html_to_abbreviation(&html), "ul>.x{a}*20000");
+
+    let items: String = (1..=20_000)
+        .map(|n| format!("<li id=\"i{}\">Item {}</li>", n, n))
+        .collect();
+    assert_eq!(html_to_abbreviation(&items), "li#i${Item $}*20000");
+}
+
+#[test]
+fn test_numbered_sequences() {
+    assert_eq!(
+        html_to_abbreviation("<li id=\"i1\">Item 1</li><li id=\"i2\">Item 2</li>"),
+        "li#i${Item $}*2"
+    );
+    assert_eq!(
+        html_to_abbreviation("<h1>A</h1><h2>A</h2><h3>A</h3>"),
+        "h${A}*3"
+    );
+    assert_eq!(html_to_abbreviation("<p>3</p><p>2</p><p>1</p>"), "p{$@-}*3");
+    assert_eq!(
+        html_to_abbreviation("<p>x9</p><p>x8</p><p>x7</p>"),
+        "p{x$@-7}*3"
+    );
+    assert_eq!(
+        html_to_abbreviation("<p class=\"c09\"></p><p class=\"c10\"></p>"),
+        "p.c$$@9*2"
+    );
+    assert_eq!(html_to_abbreviation("<p>5</p><p>6</p>"), "p{$@5}*2");
+    // Numbers must count by one
+    assert_eq!(html_to_abbreviation("<p>1</p><p>3</p>"), "p{1}+p{3}");
+    // Literal `$` is escaped, and ids and classes holding it become
+    // attributes
+    assert_eq!(html_to_abbreviation("<p>$1</p><p>$2</p>"), "p{\\$$}*2");
+    assert_eq!(
+        html_to_abbreviation("<p class=\"a$\"></p><p class=\"a$\"></p>"),
+        "p[class=\"a\\$\"]*2"
+    );
+    // The padding is found across the whole run
+    assert_eq!(
+        html_to_abbreviation("<p>11</p><p>10</p><p>09</p>"),
+        "p{$$@-9}*3"
+    );
+    // A number in a nested run is not numbered by the outer one
+    assert_eq!(
+        html_to_abbreviation("<ul><li>1</li><li>1</li></ul><ul><li>2</li><li>2</li></ul>"),
+        "ul>li{1}*2^ul>li{2}*2"
+    );
+}
+
+#[test]
+fn test_unwritable_tags_are_text() {
+    assert_eq!(html_to_abbreviation("<p{>x</p{>"), "{<p\\{>x</p\\{>}");
+    assert_eq!(
+        html_to_abbreviation("<div><my$tag>x</my$tag></div>"),
+        "div{<my\\$tag>x</my\\$tag>}"
+    );
+}
+
+#[test]
+fn test_text_nodes() {
+    let elements = html_to_elements("Hello <b>x</b>");
+    assert_eq!(elements[0].kind, ElementKind::Text);
+    assert_eq!(elements[0].text.as_deref(), Some("Hello "));
+    assert!(html_to_elements("  ").is_empty());
+}
+
+/// Checks that the abbreviation converted from `html`, parsed without
+/// snippets, renders `html` again.
+fn assert_html_round_trip(html: &str) {
+    let converted = html_to_abbreviation(html);
+    let elements = EmmetParser::new(&converted)
+        .without_snippets()
+        .parse()
+        .unwrap_or_else(|err| panic!("{} converted to {}: {:?}", html, converted, err));
+    assert_eq!(
+        crate::Renderer::new().render(&elements),
+        html,
+        "{} converted to {}",
+        html,
+        converted
+    );
+}
+
+#[test]
+fn test_backslashes() {
+    assert_eq!(html_to_abbreviation("<p>a\\</p>"), "p{a\\\\}");
+    assert_eq!(
+        html_to_abbreviation("<p x=\"a\\\">t</p>"),
+        "p[x=\"a\\\\\"]{t}"
+    );
+}
+
+#[test]
+fn test_entities_in_values_are_decoded_once() {
+    let elements = html_to_elements("<p title=\"&amp;lt; &amp;x &lt;&quot;\"></p>");
+    assert_eq!(
+        elements[0].attributes[0].value.as_deref(),
+        Some("&amp;lt; &x <\"")
+    );
+}
+
+#[test]
+fn test_snippet_names_expand_through_the_default_pipeline() {
+    // Tags named like a snippet expand when parsed with snippets
+    for (html, expanded) in [
+        ("<a>x</a>", "<a href=\"\">x</a>"),
+        ("<input>", "<input type=\"text\" />"),
+        ("<img src=a.png>", "<img src=\"a.png\" alt=\"\" />"),
+    ] {
+        assert_eq!(
+            emmet_to_html(&html_to_abbreviation(html)).unwrap(),
+            expanded
+        );
+    }
+    // Other HTML renders back the same
+    for html in [
+        "<ul class=\"nav\"><li><p>a\\</p></li><li><p>b</p></li></ul>",
+        "<p title=\"&amp;lt;\">x &amp;lt; ${1} $</p>",
+        "<div><!-- note --><span x=\"a\\\">t</span></div>",
+    ] {
+        assert_eq!(emmet_to_html(&html_to_abbreviation(html)).unwrap(), html);
+    }
+}
+
+#[test]
+fn test_html_reparses_to_the_same_tree() {
+    for html in [
+        "<p{>x</p{>",
+        "<my$tag>x</my$tag>",
+        "<p>${lang} costs $5, see ${1:x}</p>",
+        "<a title=\"${1} $$\" href=\"$\">x</a>",
+        "<p id=\"a$\" class=\"b$\">x</p><p id=\"a$\" class=\"b$\">x</p>",
+        "<p>$1</p><p>$2</p>",
+        "<!-- a  b --><div><!-- ${c} $ --><p>x</p></div>",
+        "<!DOCTYPE html><!-- x --><html></html>",
+        "<p>a\\</p><p x=\"a\\\">t</p><p title=\"&amp;lt;\">\\</p>",
+    ] {
+        assert_html_round_trip(html);
+    }
+}
+
+/// Checks that converting the HTML of `abbreviation` gives an abbreviation
+/// with the same HTML.
+fn assert_round_trip(abbreviation: &str) {
+    let html = emmet_to_html(abbreviation).unwrap();
+    let converted = html_to_abbreviation(&html);
+    assert_eq!(
+        emmet_to_html(&converted).unwrap(),
+        html,
+        "{} converted to {}",
+        abbreviation,
+        converted
+    );
+}
+
+#[test]
+fn test_html_round_trip() {
+    for abbreviation in [
+        "ul>li.item$*5",
+        "ul>li.item$$@-3*4>a[href=\"#$\"]{Link $}",
+        "table>tr*3>td.c$*2{$}",
+        "select>option[value=$]{Option $}*3",
+        "(dt+dd)*2",
+        "div>p>{Hello }+b{world}+{!}",
+        "nav>ul>(li>a[title='A \"B\"']{x})*2",
+        "form>input[type=text required]+input:email+btn:s",
+        "h$*6{Heading $}",
+        "div#a.b.c[data-x=1]/",
+        "p{Cost: \\$1}*2",
+        "!",
+        "ol>li*3>p{a}+p{b}*2",
+        "div>{text {with} braces}",
+        "ul>li.é$*2{日本 $}",
+    ] {
+        assert_round_trip(abbreviation);
+    }
+}
@@ -45,7 +45,7 @@ fn test_input_with_single_attribute_to_html() {
 #[test]
 fn test_input_with_boolean_attribute_to_html() {
     let html = emmet_to_html("input[disabled]").unwrap();
-    assert_eq!(html, "<input disabled />");
+    assert_eq!(html, "<input type=\"text\" disabled />");
 }
 
 #[test]
@@ -126,13 +126,13 @@ fn test_complex_nested_structure_to_html() {
     assert!(html.contains("<nav>"));
     assert!(html.contains("<ul>"));
     assert!(html.contains("<li>"));
-    assert!(html.contains("<a>Link</a>"));
+    assert!(html.contains("<a href=\"\">Link</a>"));
 }
 
 #[test]
 fn test_form_structure_to_html() {
     let html = emmet_to_html("form#login>input[type=email][placeholder=Email]+input[type=password][placeholder=Password]+button[type=submit]{Login}").unwrap();
-    assert!(html.contains("<form id=\"login\">"));
+    assert!(html.contains("<form id=\"login\" action=\"\">"));
     assert!(html.contains("<input type=\"email\" placeholder=\"Email\" />"));
     assert!(html.contains("<input type=\"password\" placeholder=\"Password\" />"));
     assert!(html.contains("<button type=\"submit\">Login</button>"));
@@ -140,15 +140,14 @@ fn test_form_structure_to_html() {
 
 #[test]
 fn test_table_structure_to_html() {
-    let html = emmet_to_html("table>thead>tr>th*3{Header}+tbody>tr*2>td*3{Cell}").unwrap();
+    let html = emmet_to_html("table>thead>tr>th*3{Header}^^tbody>tr*2>td*3{Cell}").unwrap();
     assert!(html.contains("<table>"));
     assert!(html.contains("<thead>"));
     assert!(html.contains("<tr>"));
-    assert!(html.contains("<th></th>"));
-    assert!(html.contains("<div>Header</div>"));
+    assert!(html.contains("<th>Header</th>"));
+    assert!(html.contains("</thead><tbody>"));
     assert!(html.contains("<tbody>"));
-    assert!(html.contains("<td></td>"));
-    assert!(html.contains("<div>Cell</div>"));
+    assert!(html.contains("<td>Cell</td>"));
 }
 
 #[test]
@@ -186,7 +185,7 @@ fn test_unclosed_text_brace() {
     let result = emmet_to_html("div{Hello");
     assert!(result.is_err());
     match result {
-        Err(EmmetError::UnclosedBracket) => {}
+        Err(EmmetError::UnclosedBracket(_)) => {}
         _ => panic!("Expected UnclosedBracket error"),
     }
 }
@@ -196,3 +195,48 @@ fn test_invalid_multiplier() {
     let result = emmet_to_html("div*abc");
     assert!(result.is_err());
 }
+
+#[test]
+fn test_climb_up_to_html() {
+    let html = emmet_to_html("div>p>span^h2").unwrap();
+    assert_eq!(html, "<div><p><span></span></p><h2></h2></div>");
+
+    let html = emmet_to_html("ul>li>a^^footer").unwrap();
+    assert_eq!(html, "<ul><li><a href=\"\"></a></li></ul><footer></footer>");
+}
+
+#[test]
+fn test_groups_to_html() {
+    let html = emmet_to_html("(header>nav)+main+(footer>p)").unwrap();
+    assert_eq!(
+        html,
+        "<header><nav></nav></header><main></main><footer><p></p></footer>"
+    );
+
+    let html = emmet_to_html("dl>(dt+dd)*3").unwrap();
+    assert_eq!(
+        html,
+        "<dl><dt></dt><dd></dd><dt></dt><dd></dd><dt></dt><dd></dd></dl>"
+    );
+
+    let html = emmet_to_html("((a+b)*2)*2").unwrap();
+    assert_eq!(html, "<a href=\"\"></a><b></b>".repeat(4));
+}
+
+#[test]
+fn test_implicit_tags_to_html() {
+    let html = emmet_to_html("ul>.item*2").unwrap();
+    assert_eq!(
+        html,
+        "<ul><li class=\"item\"></li><li class=\"item\"></li></ul>"
+    );
+
+    let html = emmet_to_html("table>.row>.cell").unwrap();
+    assert_eq!(
+        html,
+        "<table><tr class=\"row\"><td class=\"cell\"></td></tr></table>"
+    );
+
+    let html = emmet_to_html("em>.inner").unwrap();
+    assert_eq!(html, "<em><span class=\"inner\"></span></em>");
+}
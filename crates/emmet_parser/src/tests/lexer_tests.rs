@@ -0,0 +1,240 @@
+use crate::{tokenize, Lexer, TokenKind};
+
+use TokenKind::*;
+
+fn lex(input: &str) -> Vec<(TokenKind, &str)> {
+    Lexer::new(input)
+        .map(|token| (token.kind, &input[token.span.byte_range()]))
+        .collect()
+}
+
+#[test]
+fn test_operators_and_groups() {
+    assert_eq!(
+        lex("(a+b)*2>c^^d"),
+        [
+            (GroupStart, "("),
+            (TagName, "a"),
+            (Sibling, "+"),
+            (TagName, "b"),
+            (GroupEnd, ")"),
+            (Asterisk, "*"),
+            (Number, "2"),
+            (Child, ">"),
+            (TagName, "c"),
+            (ClimbUp, "^"),
+            (ClimbUp, "^"),
+            (TagName, "d"),
+        ]
+    );
+}
+
+#[test]
+fn test_element_parts() {
+    assert_eq!(
+        lex("my-el.w-1/2.b$$@-3#main/ |e"),
+        [
+            (TagName, "my-el"),
+            (Dot, "."),
+            (ClassName, "w-1/2"),
+            (Dot, "."),
+            (ClassName, "b$$@-3"),
+            (Hash, "#"),
+            (Id, "main"),
+            (Slash, "/"),
+            (Whitespace, " "),
+            (Pipe, "|"),
+            (FilterName, "e"),
+        ]
+    );
+}
+
+#[test]
+fn test_attributes() {
+    assert_eq!(
+        lex("a[href=x title=\"a \\\"b\\\"\", hidden.]"),
+        [
+            (TagName, "a"),
+            (AttributesStart, "["),
+            (AttributeName, "href"),
+            (Equals, "="),
+            (AttributeValue, "x"),
+            (Whitespace, " "),
+            (AttributeName, "title"),
+            (Equals, "="),
+            (Quote, "\""),
+            (AttributeValue, "a \\\"b\\\""),
+            (Quote, "\""),
+            (Comma, ","),
+            (Whitespace, " "),
+            (AttributeName, "hidden"),
+            (Dot, "."),
+            (AttributesEnd, "]"),
+        ]
+    );
+}
+
+#[test]
+fn test_unquoted_value_with_spaces() {
+    assert_eq!(
+        lex("[title=Hello world data-x=${1:y}]"),
+        [
+            (AttributesStart, "["),
+            (AttributeName, "title"),
+            (Equals, "="),
+            (AttributeValue, "Hello world"),
+            (Whitespace, " "),
+            (AttributeName, "data-x"),
+            (Equals, "="),
+            (AttributeValue, "${1:y}"),
+            (AttributesEnd, "]"),
+        ]
+    );
+}
+
+#[test]
+fn test_text() {
+    assert_eq!(
+        lex("p{a {b} > c}+{}"),
+        [
+            (TagName, "p"),
+            (TextStart, "{"),
+            (Text, "a {b} > c"),
+            (TextEnd, "}"),
+            (Sibling, "+"),
+            (TextStart, "{"),
+            (TextEnd, "}"),
+        ]
+    );
+}
+
+#[test]
+fn test_escaped_braces_and_trailing_slash() {
+    assert_eq!(
+        lex("p{a\\} \\{b}.x/"),
+        [
+            (TagName, "p"),
+            (TextStart, "{"),
+            (Text, "a\\} \\{b"),
+            (TextEnd, "}"),
+            (Dot, "."),
+            (ClassName, "x"),
+            (Slash, "/"),
+        ]
+    );
+    assert_eq!(
+        lex("[x=1 n$=2]"),
+        [
+            (AttributesStart, "["),
+            (AttributeName, "x"),
+            (Equals, "="),
+            (AttributeValue, "1"),
+            (Whitespace, " "),
+            (AttributeName, "n$"),
+            (Equals, "="),
+            (AttributeValue, "2"),
+            (AttributesEnd, "]"),
+        ]
+    );
+}
+
+#[test]
+fn test_escaped_backslashes() {
+    assert_eq!(
+        lex("p{a\\\\}[x=\"b\\\\\"]"),
+        [
+            (TagName, "p"),
+            (TextStart, "{"),
+            (Text, "a\\\\"),
+            (TextEnd, "}"),
+            (AttributesStart, "["),
+            (AttributeName, "x"),
+            (Equals, "="),
+            (Quote, "\""),
+            (AttributeValue, "b\\\\"),
+            (Quote, "\""),
+            (AttributesEnd, "]"),
+        ]
+    );
+}
+
+#[test]
+fn test_incomplete_input() {
+    assert_eq!(lex("li."), [(TagName, "li"), (Dot, ".")]);
+    assert_eq!(
+        lex("p{unclosed"),
+        [(TagName, "p"), (TextStart, "{"), (Text, "unclosed")]
+    );
+    assert_eq!(
+        lex("a[title='x"),
+        [
+            (TagName, "a"),
+            (AttributesStart, "["),
+            (AttributeName, "title"),
+            (Equals, "="),
+            (Quote, "'"),
+            (AttributeValue, "x"),
+        ]
+    );
+    assert_eq!(
+        lex("div[=@]"),
+        [
+            (TagName, "div"),
+            (AttributesStart, "["),
+            (Equals, "="),
+            (Unknown, "@"),
+            (AttributesEnd, "]"),
+        ]
+    );
+}
+
+#[test]
+fn test_unknown_characters() {
+    assert_eq!(
+        lex("div>@+p]"),
+        [
+            (TagName, "div"),
+            (Child, ">"),
+            (Unknown, "@"),
+            (Sibling, "+"),
+            (TagName, "p"),
+            (Unknown, "]"),
+        ]
+    );
+}
+
+#[test]
+fn test_multiplier_counts_are_ascii_digits() {
+    assert_eq!(
+        lex("p*٣"),
+        [(TagName, "p"), (Asterisk, "*"), (TagName, "٣")]
+    );
+}
+
+#[test]
+fn test_multibyte_spans() {
+    let tokens = tokenize("p.café{日本}");
+    let class = tokens[2];
+    assert_eq!(class.kind, ClassName);
+    assert_eq!(class.span.byte_range(), 2..7);
+    assert_eq!(class.span.char_range(), 2..6);
+    assert_eq!(tokens[4].span.char_range(), 7..9);
+}
+
+#[test]
+fn test_tokens_cover_input() {
+    let input = "ul#nav>li.item$*3>a[href=#${1} title='x]{Link ${0}}^p|e) €";
+    for end in input.char_indices().map(|(i, _)| i).chain([input.len()]) {
+        let prefix = &input[..end];
+        let mut position = 0;
+        let mut chars = 0;
+        for token in tokenize(prefix) {
+            assert!(!token.span.is_empty(), "empty token in {:?}", prefix);
+            assert_eq!(token.span.start, position, "gap in {:?}", prefix);
+            assert_eq!(token.span.char_start, chars, "gap in {:?}", prefix);
+            position = token.span.end;
+            chars = token.span.char_end;
+        }
+        assert_eq!(position, prefix.len());
+    }
+}
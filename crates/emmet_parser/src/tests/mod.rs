@@ -1,4 +1,26 @@
 // Test modules
+pub mod borrowed_tests;
+pub mod element_tests;
 pub mod emmet_compatibility_tests;
+pub mod error_tests;
+pub mod escape_tests;
+pub mod expand_tests;
+pub mod field_tests;
+pub mod format_tests;
+pub mod from_html_tests;
 pub mod html_conversion_tests;
+pub mod lexer_tests;
 pub mod parser_tests;
+pub mod printer_tests;
+pub mod profile_tests;
+pub mod recovery_tests;
+pub mod render_tests;
+#[cfg(feature = "serde")]
+pub mod serde_tests;
+pub mod snippet_tests;
+pub mod span_tests;
+pub mod unicode_tests;
+#[cfg(all(feature = "json", feature = "toml"))]
+pub mod user_snippet_tests;
+pub mod variable_tests;
+pub mod visit_tests;
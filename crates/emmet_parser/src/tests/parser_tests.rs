@@ -1,4 +1,4 @@
-use crate::{emmet_to_html, parse_emmet};
+use crate::{emmet_to_html, implicit_tag, parse_emmet, parse_emmet_with_parent};
 
 #[test]
 fn test_basic_element() {
@@ -79,3 +79,176 @@ fn test_complex_example() {
     assert!(html.contains("Hello"));
     assert!(html.contains("class=\"world\""));
 }
+
+#[test]
+fn test_climb_up_one_level() {
+    let result = parse_emmet("div>p>span^h2").unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].children.len(), 2);
+    assert_eq!(result[0].children[0].tag, "p");
+    assert_eq!(result[0].children[0].children[0].tag, "span");
+    assert_eq!(result[0].children[1].tag, "h2");
+}
+
+#[test]
+fn test_climb_up_multiple_levels() {
+    let result = parse_emmet("ul>li>a^^footer").unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].tag, "ul");
+    assert_eq!(result[0].children[0].tag, "li");
+    assert_eq!(result[0].children[0].children[0].tag, "a");
+    assert_eq!(result[1].tag, "footer");
+}
+
+#[test]
+fn test_climb_up_from_leaf() {
+    let result = parse_emmet("div>p^h2").unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].children.len(), 1);
+    assert_eq!(result[1].tag, "h2");
+}
+
+#[test]
+fn test_climb_up_past_root() {
+    let result = parse_emmet("div>p>span^^^^^h1").unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[1].tag, "h1");
+}
+
+#[test]
+fn test_climb_up_then_nest() {
+    let result = parse_emmet("div>section>p>span^^aside>em").unwrap();
+    assert_eq!(result[0].children.len(), 2);
+    assert_eq!(result[0].children[1].tag, "aside");
+    assert_eq!(result[0].children[1].children[0].tag, "em");
+}
+
+#[test]
+fn test_unexpected_character_is_error() {
+    assert!(parse_emmet("div>@").is_err());
+}
+
+#[test]
+fn test_group_node() {
+    let result = parse_emmet("(header>nav)+main").unwrap();
+    assert_eq!(result.len(), 2);
+    assert!(result[0].is_group());
+    assert_eq!(result[0].children[0].tag, "header");
+    assert_eq!(result[0].children[0].children[0].tag, "nav");
+    assert_eq!(result[1].tag, "main");
+}
+
+#[test]
+fn test_group_multiplier() {
+    let result = parse_emmet("(dt+dd)*3").unwrap();
+    assert!(result[0].is_group());
+    assert_eq!(result[0].multiplier, Some(3));
+    assert_eq!(result[0].children.len(), 2);
+}
+
+#[test]
+fn test_nested_groups() {
+    let result = parse_emmet("div>((a+b)*2+em)").unwrap();
+    let outer = &result[0].children[0];
+    assert!(outer.is_group());
+    assert!(outer.children[0].is_group());
+    assert_eq!(outer.children[0].multiplier, Some(2));
+    assert_eq!(outer.children[1].tag, "em");
+}
+
+#[test]
+fn test_climb_up_stays_inside_group() {
+    let result = parse_emmet("(div>p^^^span)+em").unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].children.len(), 2);
+    assert_eq!(result[0].children[1].tag, "span");
+}
+
+#[test]
+fn test_unbalanced_groups_are_errors() {
+    assert!(parse_emmet("(div>p").is_err());
+    assert!(parse_emmet("div)").is_err());
+}
+
+#[test]
+fn test_siblings_stay_inside_parent() {
+    let result = parse_emmet("div>p+span").unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].children.len(), 2);
+    assert_eq!(result[0].children[1].tag, "span");
+}
+
+#[test]
+fn test_element_parts_in_any_order() {
+    let result = parse_emmet("li*3{item}.entry").unwrap();
+    assert_eq!(result[0].multiplier, Some(3));
+    assert_eq!(result[0].text, Some("item".to_string()));
+    assert_eq!(result[0].classes, vec!["entry"]);
+}
+
+#[test]
+fn test_numbering_markers_are_kept_in_ast() {
+    let result = parse_emmet("li#item$$.a$@-3[data-n=$@2]*3").unwrap();
+    assert_eq!(result[0].id, Some("item$$".to_string()));
+    assert_eq!(result[0].classes, vec!["a$@-3"]);
+    assert_eq!(result[0].attributes[0].value, Some("$@2".to_string()));
+    assert_eq!(result[0].multiplier, Some(3));
+}
+
+#[test]
+fn test_implicit_tag_from_parent() {
+    let result = parse_emmet("ul>.item*3").unwrap();
+    assert_eq!(result[0].children[0].tag, "li");
+
+    let result = parse_emmet("table>.row>.cell").unwrap();
+    assert_eq!(result[0].children[0].tag, "tr");
+    assert_eq!(result[0].children[0].children[0].tag, "td");
+
+    let result = parse_emmet(".wrapper").unwrap();
+    assert_eq!(result[0].tag, "div");
+}
+
+#[test]
+fn test_implicit_tag_through_group() {
+    let result = parse_emmet("ol>(.a+.b)*2").unwrap();
+    let group = &result[0].children[0];
+    assert_eq!(group.children[0].tag, "li");
+    assert_eq!(group.children[1].tag, "li");
+}
+
+#[test]
+fn test_implicit_tag_with_supplied_parent() {
+    let result = parse_emmet_with_parent(".item+.item>.inner", "ul").unwrap();
+    assert_eq!(result[0].tag, "li");
+    assert_eq!(result[1].tag, "li");
+    assert_eq!(result[1].children[0].tag, "div");
+
+    let result = parse_emmet_with_parent("#opt", "SELECT").unwrap();
+    assert_eq!(result[0].tag, "option");
+}
+
+#[test]
+fn test_implicit_tag_mapping() {
+    let cases = [
+        ("ul", "li"),
+        ("ol", "li"),
+        ("table", "tr"),
+        ("tbody", "tr"),
+        ("thead", "tr"),
+        ("tfoot", "tr"),
+        ("tr", "td"),
+        ("select", "option"),
+        ("optgroup", "option"),
+        ("span", "span"),
+        ("em", "span"),
+        ("audio", "source"),
+        ("video", "source"),
+        ("map", "area"),
+        ("colgroup", "col"),
+        ("section", "div"),
+    ];
+
+    for (parent, expected) in cases {
+        assert_eq!(implicit_tag(Some(parent)), expected, "parent: {}", parent);
+    }
+}
@@ -0,0 +1,339 @@
+use crate::{
+    parse_emmet, to_abbreviation, Attribute, ElementKind, EmmetElement, EmmetParser, ErrorCode,
+    VisitorMut,
+};
+
+/// Clears every span, which differ between an abbreviation and its printed
+/// form.
+struct StripSpans;
+
+impl StripSpans {
+    fn strip(node: &mut EmmetElement) {
+        node.span = Default::default();
+        node.spans = Default::default();
+    }
+}
+
+impl VisitorMut for StripSpans {
+    fn visit_element_mut(&mut self, element: &mut EmmetElement) {
+        Self::strip(element);
+        self.walk_element_mut(element);
+    }
+
+    fn visit_group_mut(&mut self, group: &mut EmmetElement) {
+        Self::strip(group);
+        self.walk_element_mut(group);
+    }
+
+    fn visit_text_mut(&mut self, text: &mut EmmetElement) {
+        Self::strip(text);
+        self.walk_element_mut(text);
+    }
+
+    fn visit_attribute_mut(&mut self, attribute: &mut Attribute) {
+        attribute.span = Default::default();
+        attribute.spans = Default::default();
+    }
+}
+
+fn parse(input: &str) -> Vec<EmmetElement> {
+    let mut elements = EmmetParser::new(input)
+        .without_snippets()
+        .parse()
+        .unwrap_or_else(|err| panic!("{:?} does not parse: {}", input, err));
+    StripSpans.visit_nodes_mut(&mut elements);
+    elements
+}
+
+fn print(input: &str) -> String {
+    to_abbreviation(&parse(input))
+}
+
+/// Checks that `elements` print to an abbreviation parsing back to them,
+/// which prints the same again.
+fn assert_round_trip(elements: &[EmmetElement]) {
+    let printed = to_abbreviation(elements);
+    let parsed = parse(&printed);
+    assert_eq!(parsed, elements, "{:?}", printed);
+    assert_eq!(to_abbreviation(&parsed), printed);
+}
+
+#[test]
+fn test_canonical_form() {
+    for (input, expected) in [
+        ("div", "div"),
+        ("div.a#b[x=1]", "#b.a[x=1]"),
+        ("p{Hi}*2", "p{Hi}*2"),
+        ("br/", "br/"),
+        ("a>b>c^^d", "a>b>c^^d"),
+        ("a>(b>c)+d", "a>(b>c)+d"),
+        ("(a+b)*3", "(a+b)*3"),
+        ("img.a/", "img.a/"),
+        ("div.a/", ".a/"),
+        ("ul>li*3>a^^footer", "ul>li*3>a^^footer"),
+        ("div>p>span+em^bq", "div>p>span+em^bq"),
+        ("{Hello}+p", "{Hello}+p"),
+        (
+            "p[hidden.][title][data-x=\"\"]",
+            "p[hidden. title data-x=\"\"]",
+        ),
+        ("li.item$$@-3{Item $}", "li.item$$@-3{Item $}"),
+        ("p[x=1][y]", "p[x=\"1\" y]"),
+        ("p[x=1 n$=2]", "p[x=1 n$=2]"),
+    ] {
+        assert_eq!(print(input), expected, "{}", input);
+    }
+}
+
+#[test]
+fn test_implicit_tags_are_omitted() {
+    assert_eq!(print("ul>li.a+li"), "ul>.a+li");
+    assert_eq!(print("table>tr.r>td#c"), "table>.r>#c");
+    assert_eq!(print("em>span.x+div.y"), "em>.x+div.y");
+    assert_eq!(print("ul>(li.a+li.b)"), "ul>(.a+.b)");
+    assert_eq!(print("div{Text}"), "div{Text}");
+}
+
+#[test]
+fn test_attribute_quoting() {
+    assert_eq!(print("a[title=\"Hi there\"]"), "a[title=\"Hi there\"]");
+    assert_eq!(print("a[title='say \"hi\"']"), "a[title='say \"hi\"']");
+    assert_eq!(
+        print("a[title=\"it's \\\"x\\\"\"]"),
+        "a[title=\"it's \\\"x\\\"\"]"
+    );
+    assert_eq!(print("a[href=${1:url}]"), "a[href=\"${1:url}\"]");
+    // A backslash before the closing quote is escaped
+    assert_eq!(
+        parse("p[x=\"a\\\\\"]")[0].attributes[0].value.as_deref(),
+        Some("a\\")
+    );
+    assert_eq!(print("p[x=\"a\\\\\"]"), "p[x=\"a\\\\\"]");
+}
+
+#[test]
+fn test_text_escaping() {
+    assert_eq!(print("p{${1:name}}"), "p{${1:name}}");
+    assert_eq!(parse("p{a\\}b}")[0].text.as_deref(), Some("a}b"));
+    assert_eq!(print("p{a\\}b}"), "p{a\\}b}");
+    assert_eq!(print("p{\\{x}"), "p{\\{x}");
+    assert_eq!(parse("p{a\\\\}")[0].text.as_deref(), Some("a\\"));
+    assert_eq!(print("p{a\\\\}"), "p{a\\\\}");
+    // Other backslashes are kept as written
+    assert_eq!(print("p{\\$ \\n}"), "p{\\$ \\n}");
+}
+
+#[test]
+fn test_display_and_from_str() {
+    let element: EmmetElement = "ul>li.item*2".parse().unwrap();
+    assert_eq!(element.to_string(), "ul>.item*2");
+    assert_eq!(element.to_abbreviation(), "ul>.item*2");
+
+    // Snippets are not expanded
+    let element: EmmetElement = "input".parse().unwrap();
+    assert!(element.attributes.is_empty());
+
+    let group: EmmetElement = "a+b".parse().unwrap();
+    assert!(group.is_group());
+    assert_eq!(group.to_string(), "(a+b)");
+
+    let err = "".parse::<EmmetElement>().unwrap_err();
+    assert_eq!(err.parse_error().unwrap().code, ErrorCode::ExpectedElement);
+    assert!("div>@".parse::<EmmetElement>().is_err());
+}
+
+#[test]
+fn test_expanded_snippets_round_trip() {
+    for input in [
+        "ul>li.item$*3>a:link",
+        "form:post>input:email+btn:s",
+        "table+",
+    ] {
+        let mut elements = parse_emmet(input).unwrap();
+        StripSpans.visit_nodes_mut(&mut elements);
+        assert_round_trip(&elements);
+    }
+
+    // Apart from its doctype, which reads back as text
+    let mut page = parse_emmet("!").unwrap();
+    StripSpans.visit_nodes_mut(&mut page);
+    assert_eq!(page[0].children.remove(0).kind, ElementKind::Doctype);
+    assert_round_trip(&page);
+}
+
+#[test]
+fn test_snippet_markup_prints_as_text() {
+    let elements = parse_emmet("!!!+c{a}").unwrap();
+    let printed = to_abbreviation(&elements);
+    assert_eq!(printed, "{<!DOCTYPE html>}+{<!-- a -->}");
+    let parsed = parse(&printed);
+    assert!(parsed.iter().all(EmmetElement::is_text));
+    assert_eq!(to_abbreviation(&parsed), printed);
+}
+
+/// A small xorshift generator, so that generated cases are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+
+    fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
+
+const TAGS: &[&str] = &[
+    "div", "ul", "li", "p", "span", "em", "table", "tr", "td", "a$",
+];
+const NAMES: &[&str] = &["a", "item", "x-1", "é", "n_$", "w-1/2", "ça"];
+const VALUES: &[&str] = &[
+    "",
+    "x",
+    "Hello world",
+    "say \"hi\"",
+    "it's",
+    "both ' and \"",
+    "${1:url}",
+    "a=b",
+    "[x]",
+    "a\\\"b",
+    "日本",
+    "item$@-2",
+    "a\\",
+    "C:\\\\dir\\",
+    "\\'\\\"",
+];
+const TEXTS: &[&str] = &[
+    "Hi",
+    "${0}",
+    "a { b",
+    "} a",
+    "{x}",
+    "a\\{b",
+    "Привет $",
+    "x > y + z",
+    "",
+    "a\\",
+    "\\\\",
+    "\\}",
+];
+
+fn random_text(rng: &mut Rng) -> String {
+    (0..1 + rng.below(3)).map(|_| rng.pick(TEXTS)).collect()
+}
+
+fn random_element(rng: &mut Rng, depth: usize) -> EmmetElement {
+    let mut element = match rng.below(10) {
+        0 if depth > 0 => {
+            let children = (0..1 + rng.below(3))
+                .map(|_| random_element(rng, depth - 1))
+                .collect();
+            EmmetElement::group(children)
+        }
+        1 => {
+            let mut text = EmmetElement::new("");
+            text.kind = crate::ElementKind::Text;
+            text.text = Some(random_text(rng));
+            text
+        }
+        _ => {
+            let mut element = EmmetElement::new(rng.pick(TAGS));
+            if rng.chance(30) {
+                element.id = Some(rng.pick(NAMES).replace('/', ""));
+            }
+            for _ in 0..rng.below(3) {
+                element.classes.push(rng.pick(NAMES).to_string());
+            }
+            for _ in 0..rng.below(3) {
+                let value = rng.chance(70).then(|| rng.pick(VALUES).to_string());
+                element.attributes.push(Attribute {
+                    boolean: value.is_none() && rng.chance(50),
+                    name: rng.pick(NAMES).replace('/', ""),
+                    value,
+                    span: Default::default(),
+                    spans: Default::default(),
+                });
+            }
+            if rng.chance(30) {
+                element.text = Some(random_text(rng));
+            }
+            element.self_closing = rng.chance(10);
+            element
+        }
+    };
+
+    if rng.chance(20) {
+        element.multiplier = Some(rng.below(5) as u32);
+    }
+    if depth > 0 && !element.is_group() && rng.chance(40) {
+        element.children = (0..1 + rng.below(3))
+            .map(|_| random_element(rng, depth - 1))
+            .collect();
+    }
+    element
+}
+
+#[test]
+fn test_generated_trees_round_trip() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    for _ in 0..2000 {
+        let elements: Vec<_> = (0..1 + rng.below(3))
+            .map(|_| random_element(&mut rng, 3))
+            .collect();
+        assert_round_trip(&elements);
+    }
+}
+
+#[test]
+fn test_generated_abbreviations_are_stable() {
+    const PIECES: &[&str] = &[
+        "div",
+        "li",
+        "p",
+        ".a",
+        "#b",
+        "[x=1]",
+        "[y='a b']",
+        "[z.]",
+        "{t}",
+        "{${1}}",
+        "*2",
+        "/",
+        ">",
+        ">",
+        "+",
+        "+",
+        "^",
+        "(",
+        ")",
+        "$",
+        "td",
+    ];
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let mut parsed = 0;
+
+    for _ in 0..5000 {
+        let input: String = (0..1 + rng.below(10)).map(|_| rng.pick(PIECES)).collect();
+        let Ok(first) = EmmetParser::new(&input).without_snippets().parse() else {
+            continue;
+        };
+        parsed += 1;
+        let mut first = first;
+        StripSpans.visit_nodes_mut(&mut first);
+
+        let printed = to_abbreviation(&first);
+        let second = parse(&printed);
+        assert_eq!(second, first, "{:?} printed as {:?}", input, printed);
+        assert_eq!(to_abbreviation(&second), printed, "{:?}", input);
+    }
+    assert!(parsed > 1000);
+}
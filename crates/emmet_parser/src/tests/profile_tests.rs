@@ -0,0 +1,106 @@
+use crate::{
+    emmet_to_html, parse_emmet, Case, EmmetParser, FormatOptions, LspFields, Profile, Quote,
+    Renderer, SelfClosingStyle,
+};
+
+fn render(profile: Profile, input: &str) -> String {
+    let elements = parse_emmet(input).unwrap();
+    Renderer::new().with_profile(profile).render(&elements)
+}
+
+#[test]
+fn test_self_closing_styles() {
+    let input = "br+img[src=a.png]+my-icon/+p";
+
+    assert_eq!(
+        render(Profile::html(), input),
+        "<br><img src=\"a.png\" alt=\"\"><my-icon><p></p>"
+    );
+    assert_eq!(
+        render(Profile::xhtml(), input),
+        "<br /><img src=\"a.png\" alt=\"\" /><my-icon /><p></p>"
+    );
+    assert_eq!(
+        render(Profile::xml(), input),
+        "<br/><img src=\"a.png\" alt=\"\"/><my-icon/><p/>"
+    );
+}
+
+#[test]
+fn test_xml_closes_only_empty_elements() {
+    assert_eq!(
+        render(Profile::xml(), "Item>Name{Box}+Tags>Tag*2"),
+        "<Item><Name>Box</Name><Tags><Tag/><Tag/></Tags></Item>"
+    );
+    // Void HTML tags with content are not special in XML
+    let elements = EmmetParser::new("br{x}")
+        .without_snippets()
+        .parse()
+        .unwrap();
+    assert_eq!(
+        Renderer::new()
+            .with_profile(Profile::xml())
+            .render(&elements),
+        "<br>x</br>"
+    );
+}
+
+#[test]
+fn test_default_profile_matches_to_html() {
+    assert_eq!(Profile::default(), Profile::xhtml());
+    assert_eq!(render(Profile::default(), "!"), emmet_to_html("!").unwrap());
+}
+
+#[test]
+fn test_profiles_for_syntax() {
+    assert_eq!(Profile::for_syntax("html"), Profile::html());
+    assert_eq!(Profile::for_syntax("xhtml"), Profile::xhtml());
+    assert_eq!(Profile::for_syntax("JSX"), Profile::xhtml());
+    assert_eq!(
+        Profile::for_syntax("xsl").self_closing,
+        SelfClosingStyle::Xml
+    );
+    assert_eq!(Profile::for_syntax("vue"), Profile::html());
+}
+
+#[test]
+fn test_name_case_and_quotes() {
+    let profile = Profile::html()
+        .with_tag_case(Case::Upper)
+        .with_attribute_case(Case::Lower)
+        .with_quote(Quote::Single);
+
+    assert_eq!(
+        render(profile, "div#main.box[Data-Id=1][HIDDEN]>br"),
+        "<DIV id='main' class='box' data-id='1' hidden><BR></DIV>"
+    );
+    assert_eq!(
+        render(Profile::xml().with_tag_case(Case::Lower), "svg:Rect"),
+        "<svg:rect/>"
+    );
+}
+
+#[test]
+fn test_quotes() {
+    assert_eq!(Profile::default().quote, Quote::Double);
+    assert_eq!(Quote::default().as_char(), '"');
+    assert_eq!(Quote::Single.as_char(), '\'');
+    assert_eq!(
+        render(Profile::html().with_quote(Quote::Double), "a[title='x']"),
+        "<a href=\"\" title=\"x\"></a>"
+    );
+}
+
+#[test]
+fn test_profile_with_format_and_fields() {
+    let elements = parse_emmet("ul>li>br").unwrap();
+    let format = FormatOptions::default();
+    assert_eq!(
+        Renderer::new()
+            .with_profile(Profile::xml())
+            .with_format(&format)
+            .with_fields(&LspFields)
+            .render(&elements),
+        "<ul>\n\t<li><br/></li>\n</ul>"
+    );
+}
@@ -0,0 +1,220 @@
+use crate::{
+    parse_emmet, EmmetParser, ErrorCode, ExpandConfig, Expected, PartialParse, Renderer,
+    SnippetRegistry,
+};
+
+fn parse_partial(input: &str) -> PartialParse {
+    EmmetParser::new(input).parse_partial()
+}
+
+fn codes(result: &PartialParse) -> Vec<ErrorCode> {
+    result.diagnostics.iter().map(|d| d.code).collect()
+}
+
+fn html(result: &PartialParse) -> String {
+    Renderer::new().render(&result.elements)
+}
+
+#[test]
+fn test_incomplete_input() {
+    let result = parse_partial("div>ul>li.");
+    assert_eq!(html(&result), "<div><ul><li></li></ul></div>");
+    assert_eq!(codes(&result), [ErrorCode::ExpectedClassName]);
+    assert_eq!(result.expected, [Expected::ClassName]);
+
+    let result = parse_partial("a[href=");
+    assert_eq!(html(&result), "<a href=\"\"></a>");
+    assert_eq!(codes(&result), [ErrorCode::ExpectedAttributeValue]);
+    assert_eq!(result.expected, [Expected::AttributeValue]);
+
+    let result = parse_partial("ul>li*");
+    assert_eq!(html(&result), "<ul><li></li></ul>");
+    assert_eq!(result.expected, [Expected::Number]);
+
+    let result = parse_partial("p#");
+    assert_eq!(result.elements[0].id, None);
+    assert_eq!(result.expected, [Expected::Id]);
+}
+
+#[test]
+fn test_unclosed_brackets() {
+    let result = parse_partial("(header>nav");
+    assert_eq!(html(&result), "<header><nav></nav></header>");
+    assert_eq!(codes(&result), [ErrorCode::UnclosedGroup]);
+    assert!(result.expected.contains(&Expected::Char(')')));
+    assert!(result.expected.contains(&Expected::TagName));
+    assert!(!result.expected.contains(&Expected::Char('|')));
+
+    let result = parse_partial("p{Hello");
+    assert_eq!(html(&result), "<p>Hello</p>");
+    assert_eq!(codes(&result), [ErrorCode::UnclosedText]);
+    assert_eq!(result.expected, [Expected::Text, Expected::Char('}')]);
+
+    let result = parse_partial("div[title='a b");
+    assert_eq!(html(&result), "<div title=\"a b\"></div>");
+    assert_eq!(codes(&result), [ErrorCode::UnclosedQuote]);
+    assert_eq!(
+        result.expected,
+        [Expected::AttributeValue, Expected::Char('\'')]
+    );
+
+    // Only the first error at the end of input is reported
+    let result = parse_partial("div[x=1");
+    assert!(result.diagnostics.len() == 1);
+    assert_eq!(
+        result.expected,
+        [
+            Expected::AttributeValue,
+            Expected::AttributeName,
+            Expected::Char(']')
+        ]
+    );
+}
+
+#[test]
+fn test_complete_input() {
+    let result = parse_partial("ul>li");
+    assert!(result.diagnostics.is_empty());
+    assert_eq!(html(&result), "<ul><li></li></ul>");
+    for token in [
+        Expected::TagName,
+        Expected::Char('.'),
+        Expected::Char('*'),
+        Expected::Char('>'),
+        Expected::Char('+'),
+        Expected::Char('^'),
+        Expected::Char('|'),
+    ] {
+        assert!(result.expected.contains(&token), "{:?}", token);
+    }
+
+    // Filters may follow any complete abbreviation, even an empty one
+    for input in ["", "p+"] {
+        assert_eq!(
+            parse_partial(input).expected,
+            [Expected::Element, Expected::Char('|')]
+        );
+    }
+    // Operators cannot follow a dangling operator
+    for input in ["div>", "ul>li>", "(p)+div>"] {
+        assert_eq!(
+            parse_partial(input).expected,
+            [Expected::Element, Expected::Char('|')],
+            "{}",
+            input
+        );
+    }
+    assert_eq!(
+        parse_partial("p|e").expected,
+        [Expected::FilterName, Expected::Char('|')]
+    );
+}
+
+#[test]
+fn test_errors_in_the_middle() {
+    let result = parse_partial("div>@+p.");
+    assert_eq!(html(&result), "<div><p></p></div>");
+    assert_eq!(
+        codes(&result),
+        [ErrorCode::ExpectedElement, ErrorCode::ExpectedClassName]
+    );
+    assert_eq!(result.diagnostics[0].span.byte_range(), 4..5);
+
+    let result = parse_partial("p*2*3");
+    assert_eq!(html(&result), "<p></p><p></p>");
+    assert_eq!(codes(&result), [ErrorCode::RepeatedMultiplier]);
+
+    let result = parse_partial("(p)(em)");
+    assert_eq!(html(&result), "<p></p><em></em>");
+    assert_eq!(codes(&result), [ErrorCode::UnexpectedCharacter]);
+
+    let result = parse_partial("(p)>em+div)+b");
+    assert_eq!(html(&result), "<p><em></em><div></div></p><b></b>");
+    assert_eq!(
+        codes(&result),
+        [ErrorCode::GroupChildren, ErrorCode::UnmatchedParen]
+    );
+
+    let result = parse_partial("div[=x title=y]*a");
+    assert_eq!(result.elements[0].attributes[0].name, "title");
+    assert_eq!(
+        codes(&result),
+        [ErrorCode::ExpectedName, ErrorCode::InvalidNumber]
+    );
+}
+
+#[test]
+fn test_deep_nesting() {
+    let deepest = format!("{}p", "div>".repeat(127));
+    assert!(parse_partial(&deepest).diagnostics.is_empty());
+    assert!(parse_emmet(&deepest).is_ok());
+
+    for input in [
+        "div>".repeat(4000),
+        format!("{}p{}", "(".repeat(2000), ")".repeat(2000)),
+        format!("{}p{}", "div>(".repeat(3000), ")".repeat(3000)),
+    ] {
+        let result = parse_partial(&input);
+        assert_eq!(codes(&result), [ErrorCode::NestingTooDeep]);
+        assert_eq!(
+            parse_emmet(&input).unwrap_err().parse_error().unwrap().code,
+            ErrorCode::NestingTooDeep
+        );
+    }
+
+    // Only what is nested too deeply is left out
+    let input = format!("{}p{}+em", "(".repeat(2000), ")".repeat(2000));
+    let result = parse_partial(&input);
+    assert_eq!(codes(&result), [ErrorCode::NestingTooDeep]);
+    assert_eq!(result.elements.len(), 2);
+    assert_eq!(result.elements[1].tag, "em");
+}
+
+#[test]
+fn test_recovered_variables_and_snippets() {
+    let config = ExpandConfig::new().strict(true);
+    let result = EmmetParser::new("p{${nope}}")
+        .with_config(&config)
+        .parse_partial();
+    assert_eq!(html(&result), "<p>${nope}</p>");
+    assert_eq!(codes(&result), [ErrorCode::UnknownVariable]);
+    assert_eq!(result.diagnostics[0].span.byte_range(), 1..10);
+
+    let mut snippets = SnippetRegistry::new();
+    snippets.insert("bad", "div[title=x");
+    let result = EmmetParser::new("p+bad")
+        .with_snippets(&snippets)
+        .parse_partial();
+    assert_eq!(html(&result), "<p></p><div title=\"x\"></div>");
+    assert_eq!(result.diagnostics[0].span.byte_range(), 2..5);
+    assert_eq!(
+        result.diagnostics[0].message,
+        "in snippet 'bad': expected attribute name or ']', found end of input"
+    );
+}
+
+#[test]
+fn test_strict_parse_is_unchanged() {
+    // Recovery is only used by `parse_partial`
+    assert!(EmmetParser::new("div>ul>li.").parse().is_err());
+    assert!(EmmetParser::new("(p)(em)").parse().is_err());
+}
+
+#[test]
+fn test_every_prefix_parses() {
+    let inputs = [
+        "!",
+        "html>(head>title{Page})+body>div#app.a.b[data-x='1' hidden.]*2>ul>li.item$@-*3^^footer|e",
+        "table>(tr>td{${lang}}*2)*3+a[href=\"x\\\"y\" title=t]/+{text}>p)@#.[",
+    ];
+    for input in inputs {
+        for (end, _) in input.char_indices().skip(1) {
+            let result = parse_partial(&input[..end]);
+            assert!(
+                !result.expected.is_empty() || !result.diagnostics.is_empty(),
+                "{}",
+                &input[..end]
+            );
+        }
+    }
+}
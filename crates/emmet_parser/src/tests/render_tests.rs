@@ -0,0 +1,188 @@
+use std::{fmt, io};
+
+use crate::{emmet_to_html, parse_emmet, FormatOptions, LspFields, PlainFields, Profile, Renderer};
+
+/// Accepts `limit` bytes, then fails every write.
+struct Failing {
+    written: usize,
+    limit: usize,
+}
+
+impl fmt::Write for Failing {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.written += s.len();
+        match self.written > self.limit {
+            true => Err(fmt::Error),
+            false => Ok(()),
+        }
+    }
+}
+
+impl io::Write for Failing {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written += buf.len();
+        match self.written > self.limit {
+            true => Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed")),
+            false => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_render_to_appends() {
+    let elements = parse_emmet("ul>li.item$*2").unwrap();
+    let mut html = String::from("<!-- list -->");
+    Renderer::new().render_to(&elements, &mut html).unwrap();
+    assert_eq!(
+        html,
+        "<!-- list --><ul><li class=\"item1\"></li><li class=\"item2\"></li></ul>"
+    );
+}
+
+#[test]
+fn test_streamed_output_matches_render() {
+    let format = FormatOptions::default();
+    for input in [
+        "!",
+        "table>tr*3>td{$ & <x>}*2",
+        "c+p[title='a<b']",
+        "a:link",
+    ] {
+        let elements = parse_emmet(input).unwrap();
+        for renderer in [Renderer::new(), Renderer::new().with_format(&format)] {
+            let expected = renderer.render(&elements);
+
+            let mut html = String::new();
+            renderer.render_to(&elements, &mut html).unwrap();
+            assert_eq!(html, expected);
+
+            let mut html = String::new();
+            renderer
+                .render_nodes_to(&crate::expand(&elements), &mut html)
+                .unwrap();
+            assert_eq!(html, expected);
+
+            let mut bytes = Vec::new();
+            renderer.render_to_io(&elements, &mut bytes).unwrap();
+            assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+        }
+    }
+}
+
+#[test]
+fn test_streamed_output_matches_expanded_nodes() {
+    let format = FormatOptions::default();
+    let eager = FormatOptions {
+        inline_break: 1,
+        ..FormatOptions::default()
+    };
+    let cursor = PlainFields::new("|");
+    let inputs = [
+        "!",
+        "ul>li.item$*3>a:link{Item $}",
+        "p>{Hi }+b+input+c",
+        "(h$+p{${1:x}})*2",
+        "div>{a}>span+br",
+    ];
+    for input in inputs {
+        let elements = parse_emmet(input).unwrap();
+        for renderer in [
+            Renderer::new(),
+            Renderer::new().with_format(&format).with_fields(&LspFields),
+            Renderer::new().with_format(&eager).with_fields(&cursor),
+            Renderer::new()
+                .with_profile(Profile::xml())
+                .with_fields(&cursor),
+        ] {
+            assert_eq!(
+                renderer.render(&elements),
+                renderer.render_nodes(&renderer.expand(&elements)),
+                "{}",
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn test_large_multiplier() {
+    let html = emmet_to_html("ul>li.item$$$$*5000>a{Item $}").unwrap();
+    assert!(html.starts_with("<ul><li class=\"item0001\"><a href=\"\">Item 1</a></li>"));
+    assert!(html.ends_with("<li class=\"item5000\"><a href=\"\">Item 5000</a></li></ul>"));
+    assert_eq!(html.matches("<li ").count(), 5000);
+}
+
+/// Counts the bytes written and throws them away.
+struct Counting(usize);
+
+impl fmt::Write for Counting {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+#[test]
+fn test_repetitions_are_streamed() {
+    let format = FormatOptions::default();
+    let elements = parse_emmet("p*100000+ul>(li>br)*100000").unwrap();
+    // Each repetition writes `<p></p>` and `<li><br /></li>`, formatted
+    // with a newline and an indent
+    for (renderer, repetition, rest) in [
+        (Renderer::new(), 7 + 15, "<ul></ul>".len()),
+        (
+            Renderer::new().with_format(&format),
+            8 + 17,
+            "\n<ul>\n</ul>".len() - 1,
+        ),
+    ] {
+        let mut out = Counting(0);
+        renderer.render_to(&elements, &mut out).unwrap();
+        assert_eq!(out.0, repetition * 100_000 + rest);
+    }
+
+    // Repetitions are written as they are produced, so that output too
+    // large to hold in memory stops at the first failed write
+    let elements = parse_emmet("div*4294967295").unwrap();
+    let mut out = Failing {
+        written: 0,
+        limit: 50,
+    };
+    assert_eq!(
+        Renderer::new().render_to(&elements, &mut out),
+        Err(fmt::Error)
+    );
+}
+
+#[test]
+fn test_fmt_errors_stop_rendering() {
+    let elements = parse_emmet("div*100").unwrap();
+    let mut out = Failing {
+        written: 0,
+        limit: 50,
+    };
+    assert_eq!(
+        Renderer::new().render_to(&elements, &mut out),
+        Err(fmt::Error)
+    );
+    // Nothing is written after the first failure
+    assert!(out.written < 60);
+}
+
+#[test]
+fn test_io_errors_are_returned() {
+    let elements = parse_emmet("div*100").unwrap();
+    let mut out = Failing {
+        written: 0,
+        limit: 50,
+    };
+    let err = Renderer::new()
+        .render_to_io(&elements, &mut out)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    assert_eq!(err.to_string(), "closed");
+}
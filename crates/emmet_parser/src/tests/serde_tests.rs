@@ -0,0 +1,130 @@
+use serde_json::json;
+
+use crate::{
+    expand, parse_emmet, Attribute, EmmetElement, EmmetError, EmmetParser, ErrorCode, ExpandedNode,
+    PartialParse, Versioned,
+};
+
+#[test]
+fn test_element_shape() {
+    let elements = EmmetParser::new("a.b[x=1]")
+        .without_snippets()
+        .parse()
+        .unwrap();
+    let span = |start: usize, end: usize| json!({"start": start, "end": end, "char_start": start, "char_end": end});
+
+    assert_eq!(
+        serde_json::to_value(&elements[0]).unwrap(),
+        json!({
+            "kind": "element",
+            "tag": "a",
+            "id": null,
+            "classes": ["b"],
+            "attributes": [{
+                "name": "x",
+                "value": "1",
+                "boolean": false,
+                "span": span(4, 7),
+                "spans": {"name": span(4, 5), "value": span(6, 7)},
+            }],
+            "text": null,
+            "children": [],
+            "multiplier": null,
+            "self_closing": false,
+            "span": span(0, 8),
+            "spans": {
+                "tag": span(0, 1),
+                "id": null,
+                "classes": [span(1, 3)],
+                "text": null,
+                "multiplier": null,
+                "operator": null,
+            },
+        })
+    );
+}
+
+#[test]
+fn test_elements_round_trip() {
+    let elements = parse_emmet("ul#nav>li.item$*3>a:link{Item ${1}}^(p+{text})*2").unwrap();
+    let json = serde_json::to_string(&Versioned::new(&elements)).unwrap();
+    let parsed: Versioned<Vec<EmmetElement>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.data, elements);
+}
+
+#[test]
+fn test_optional_fields_default() {
+    let element: EmmetElement = serde_json::from_value(json!({
+        "tag": "input",
+        "attributes": [{"name": "disabled"}],
+    }))
+    .unwrap();
+    assert_eq!(element.to_html(), "<input disabled />");
+
+    let attribute: Attribute = serde_json::from_value(json!({"name": "x", "value": "1"})).unwrap();
+    assert_eq!(attribute.value.as_deref(), Some("1"));
+}
+
+#[test]
+fn test_version_mismatch() {
+    let err = serde_json::from_value::<Versioned<Vec<EmmetElement>>>(json!({
+        "version": 2,
+        "data": [],
+    }))
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("unsupported format version 2, expected 1"));
+}
+
+#[test]
+fn test_error_shape() {
+    let err = parse_emmet("div[=x]").unwrap_err();
+    let value = serde_json::to_value(&err).unwrap();
+
+    assert_eq!(value["kind"], "invalid_syntax");
+    assert_eq!(value["detail"]["code"], "E006");
+    assert_eq!(
+        value["detail"]["expected"],
+        json!(["attribute_name", {"char": "]"}])
+    );
+    assert_eq!(value["detail"]["found"], "=");
+
+    let parsed: EmmetError = serde_json::from_value(value).unwrap();
+    assert_eq!(parsed.parse_error(), err.parse_error());
+
+    let unknown = serde_json::to_value(EmmetError::UnknownVariable("x".to_string())).unwrap();
+    assert_eq!(unknown, json!({"kind": "unknown_variable", "detail": "x"}));
+}
+
+#[test]
+fn test_error_codes() {
+    for code in ErrorCode::ALL {
+        let json = serde_json::to_string(&code).unwrap();
+        assert_eq!(json, format!("\"{}\"", code));
+        assert_eq!(serde_json::from_str::<ErrorCode>(&json).unwrap(), code);
+    }
+    assert!(serde_json::from_str::<ErrorCode>("\"E999\"").is_err());
+}
+
+#[test]
+fn test_partial_parse_round_trip() {
+    let result = EmmetParser::new("ul>li.").parse_partial();
+    let json = serde_json::to_string(&result).unwrap();
+    assert_eq!(serde_json::from_str::<PartialParse>(&json).unwrap(), result);
+}
+
+#[test]
+fn test_expanded_node_shape() {
+    let nodes = expand(&parse_emmet("p{Hi}+c+!!!").unwrap());
+    let json = serde_json::to_value(&nodes).unwrap();
+    assert_eq!(json[0]["element"]["tag"], "p");
+    assert_eq!(json[0]["element"]["children"], json!([{"text": "Hi"}]));
+    assert_eq!(json[1], json!({"comment": "  "}));
+    assert_eq!(json[2], json!({"doctype": "html"}));
+
+    let parsed: Vec<ExpandedNode> = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, nodes);
+    let parsed: ExpandedNode = serde_json::from_value(json!({"element": {"tag": "br"}})).unwrap();
+    assert_eq!(parsed.as_element().unwrap().tag, "br");
+}
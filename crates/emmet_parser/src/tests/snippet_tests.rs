@@ -0,0 +1,167 @@
+use crate::{emmet_to_html, parse_emmet, ElementKind, EmmetParser, SnippetRegistry};
+
+fn expand_with(registry: &SnippetRegistry, input: &str) -> String {
+    let mut parser = EmmetParser::new(input).with_snippets(registry);
+    parser
+        .parse()
+        .unwrap()
+        .iter()
+        .map(|element| element.to_html())
+        .collect()
+}
+
+#[test]
+fn test_html5_document() {
+    let expected = "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"UTF-8\" />\
+        <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\" />\
+        <title>Document</title></head><body></body></html>";
+    assert_eq!(emmet_to_html("!").unwrap(), expected);
+    assert_eq!(emmet_to_html("html:5").unwrap(), expected);
+}
+
+#[test]
+fn test_standard_aliases() {
+    let cases = [
+        ("a:link", "<a href=\"http://\"></a>"),
+        ("link:css", "<link rel=\"stylesheet\" href=\"style.css\" />"),
+        ("script:src", "<script src=\"\"></script>"),
+        ("input:email", "<input type=\"email\" name=\"\" id=\"\" />"),
+        ("btn:s", "<button type=\"submit\"></button>"),
+        ("form:post", "<form action=\"\" method=\"post\"></form>"),
+        ("bq", "<blockquote></blockquote>"),
+        ("c", "<!--  -->"),
+        ("select:d", "<select name=\"\" id=\"\" disabled></select>"),
+        (
+            "meta:edge",
+            "<meta http-equiv=\"X-UA-Compatible\" content=\"ie=edge\" />",
+        ),
+    ];
+
+    for (abbreviation, expected) in cases {
+        assert_eq!(
+            emmet_to_html(abbreviation).unwrap(),
+            expected,
+            "abbreviation: {}",
+            abbreviation
+        );
+    }
+}
+
+#[test]
+fn test_plus_snippets() {
+    let cases = [
+        ("ul+", "<ul><li></li></ul>"),
+        ("ol+", "<ol><li></li></ol>"),
+        ("dl+", "<dl><dt></dt><dd></dd></dl>"),
+        ("table+", "<table><tr><td></td></tr></table>"),
+        (
+            "select+",
+            "<select name=\"\" id=\"\"><option value=\"\"></option></select>",
+        ),
+        ("div>ul+", "<div><ul><li></li></ul></div>"),
+        ("p+ol+", "<p></p><ol><li></li></ol>"),
+        ("ul+*2", "<ul><li></li></ul><ul><li></li></ul>"),
+        // Followed by another element, `+` is the sibling operator
+        ("ul+p", "<ul></ul><p></p>"),
+    ];
+
+    for (abbreviation, expected) in cases {
+        assert_eq!(
+            emmet_to_html(abbreviation).unwrap(),
+            expected,
+            "abbreviation: {}",
+            abbreviation
+        );
+    }
+}
+
+#[test]
+fn test_snippet_merges_element_parts() {
+    assert_eq!(
+        emmet_to_html("a:link#home.nav[title=Home]{Home}").unwrap(),
+        "<a id=\"home\" class=\"nav\" href=\"http://\" title=\"Home\">Home</a>"
+    );
+    assert_eq!(
+        emmet_to_html("input:email[name=login]").unwrap(),
+        "<input type=\"email\" name=\"login\" id=\"\" />"
+    );
+    assert_eq!(emmet_to_html("c{note}").unwrap(), "<!-- note -->");
+}
+
+#[test]
+fn test_snippet_children_go_to_deepest_node() {
+    assert_eq!(
+        emmet_to_html("ul+>span").unwrap(),
+        "<ul><li><span></span></li></ul>"
+    );
+    assert_eq!(
+        emmet_to_html("!>p").unwrap(),
+        emmet_to_html("!")
+            .unwrap()
+            .replace("<body>", "<body><p></p>")
+    );
+}
+
+#[test]
+fn test_snippet_ast() {
+    let result = parse_emmet("!").unwrap();
+    assert!(result[0].is_group());
+    assert_eq!(result[0].children[0].kind, ElementKind::Doctype);
+    assert_eq!(result[0].children[0].text.as_deref(), Some("html"));
+    assert_eq!(result[0].children[1].tag, "html");
+
+    let result = parse_emmet("br").unwrap();
+    assert!(result[0].self_closing);
+}
+
+#[test]
+fn test_custom_registry() {
+    let mut registry = SnippetRegistry::new();
+    registry.insert("card|crd", "div.card>div.card-body");
+
+    assert_eq!(
+        expand_with(&registry, "crd.wide>p"),
+        "<div class=\"card wide\"><div class=\"card-body\"><p></p></div></div>"
+    );
+    // Built-in snippets are not part of a custom registry
+    assert_eq!(expand_with(&registry, "bq"), "<bq></bq>");
+}
+
+#[test]
+fn test_without_snippets() {
+    let mut parser = EmmetParser::new("a+bq").without_snippets();
+    let html: String = parser
+        .parse()
+        .unwrap()
+        .iter()
+        .map(|element| element.to_html())
+        .collect();
+    assert_eq!(html, "<a></a><bq></bq>");
+}
+
+#[test]
+fn test_recursive_snippets_stay_literal() {
+    let mut registry = SnippetRegistry::new();
+    registry.insert("box", "box.outer>box.inner");
+    registry.insert("ping", "pong[from=ping]");
+    registry.insert("pong", "ping[from=pong]");
+
+    assert_eq!(
+        expand_with(&registry, "box"),
+        "<box class=\"outer\"><box class=\"inner\"></box></box>"
+    );
+    // `pong` expands back to a literal `ping`, and the outer attribute wins
+    assert_eq!(
+        expand_with(&registry, "ping"),
+        "<ping from=\"ping\"></ping>"
+    );
+}
+
+#[test]
+fn test_registry_aliases() {
+    let registry = SnippetRegistry::html();
+    assert_eq!(registry.get("btn:s"), Some("button[type=submit]"));
+    assert_eq!(registry.get("button:submit"), Some("button[type=submit]"));
+    assert!(registry.contains("!"));
+    assert!(!registry.contains("btn:s|button:s"));
+}
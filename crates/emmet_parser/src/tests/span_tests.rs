@@ -0,0 +1,108 @@
+use crate::{parse_emmet, EmmetParser, Span};
+
+fn text(input: &str, span: Span) -> &str {
+    &input[span.byte_range()]
+}
+
+#[test]
+fn test_element_part_spans() {
+    let input = "div#main.a.b[title=\"x y\" hidden. data-n=3]{Hi}*2";
+    let elements = EmmetParser::new(input).without_snippets().parse().unwrap();
+    let div = &elements[0];
+
+    assert_eq!(text(input, div.span), input);
+    assert_eq!(text(input, div.spans.tag.unwrap()), "div");
+    assert_eq!(text(input, div.spans.id.unwrap()), "#main");
+    let classes: Vec<_> = div.spans.classes.iter().map(|s| text(input, *s)).collect();
+    assert_eq!(classes, [".a", ".b"]);
+    assert_eq!(text(input, div.spans.text.unwrap()), "{Hi}");
+    assert_eq!(text(input, div.spans.multiplier.unwrap()), "*2");
+    assert_eq!(div.spans.operator, None);
+
+    let title = &div.attributes[0];
+    assert_eq!(text(input, title.span), "title=\"x y\"");
+    assert_eq!(text(input, title.spans.name), "title");
+    assert_eq!(text(input, title.spans.value.unwrap()), "\"x y\"");
+    assert_eq!(text(input, div.attributes[1].span), "hidden.");
+    assert_eq!(div.attributes[1].spans.value, None);
+    assert_eq!(text(input, div.attributes[2].spans.value.unwrap()), "3");
+}
+
+#[test]
+fn test_operator_spans() {
+    let input = "ul>li+li>a^^p+(b+i)*2";
+    let elements = parse_emmet(input).unwrap();
+    let ul = &elements[0];
+    let operator = |element: &crate::EmmetElement| element.spans.operator.map(|s| text(input, s));
+
+    assert_eq!(text(input, ul.span), "ul>li+li>a^^");
+    assert_eq!(operator(ul), None);
+    assert_eq!(operator(&ul.children[0]), Some(">"));
+    assert_eq!(operator(&ul.children[1]), Some("+"));
+    assert_eq!(operator(&ul.children[1].children[0]), Some(">"));
+    assert_eq!(operator(&elements[1]), Some("^^"));
+    assert_eq!(operator(&elements[2]), Some("+"));
+
+    let group = &elements[2];
+    assert_eq!(text(input, group.span), "(b+i)*2");
+    assert_eq!(text(input, group.spans.multiplier.unwrap()), "*2");
+    assert_eq!(operator(&group.children[0]), None);
+    assert_eq!(operator(&group.children[1]), Some("+"));
+}
+
+#[test]
+fn test_implicit_tags_and_text_nodes() {
+    let input = "ul>.item{a}{b}+{c}";
+    let elements = parse_emmet(input).unwrap();
+    let item = &elements[0].children[0];
+
+    assert_eq!(item.spans.tag, None);
+    assert_eq!(text(input, item.span), ".item{a}{b}");
+    assert_eq!(text(input, item.spans.text.unwrap()), "{a}{b}");
+
+    let node = &elements[0].children[1];
+    assert!(node.is_text());
+    assert_eq!(text(input, node.span), "{c}");
+    assert_eq!(text(input, node.spans.text.unwrap()), "{c}");
+}
+
+#[test]
+fn test_snippet_spans_point_at_snippet_name() {
+    let input = "p+a.x[title=t]>span";
+    let elements = parse_emmet(input).unwrap();
+    let a = &elements[1];
+    let name = a.spans.tag.unwrap();
+
+    assert_eq!(text(input, name), "a");
+    assert_eq!(text(input, a.span), "a.x[title=t]>span");
+    // `href` comes from the snippet, `title` from the abbreviation
+    assert_eq!(a.attributes[0].name, "href");
+    assert_eq!(a.attributes[0].span, name);
+    assert_eq!(text(input, a.attributes[1].span), "title=t");
+    assert_eq!(text(input, a.spans.classes[0]), ".x");
+    assert_eq!(text(input, a.children[0].span), "span");
+
+    // Nodes inside a multi-element snippet
+    let input = "p+ul+";
+    let list = &parse_emmet(input).unwrap()[1];
+    assert_eq!(text(input, list.span), "ul+");
+    assert_eq!(text(input, list.children[0].span), "ul+");
+}
+
+#[test]
+fn test_char_and_byte_offsets() {
+    let span = Span {
+        start: 2,
+        end: 6,
+        char_start: 1,
+        char_end: 3,
+    };
+    assert_eq!(span.byte_range(), 2..6);
+    assert_eq!(span.char_range(), 1..3);
+    assert!(!span.is_empty());
+
+    let input = "p>b*3";
+    let b = &parse_emmet(input).unwrap()[0].children[0];
+    assert_eq!(b.span.byte_range(), b.span.char_range());
+    assert_eq!(b.span.byte_range(), 2..5);
+}
@@ -0,0 +1,94 @@
+use crate::{emmet_to_html, parse_emmet, EmmetParser, ErrorCode, Span};
+
+fn text(input: &str, span: Span) -> &str {
+    &input[span.byte_range()]
+}
+
+#[test]
+fn test_multibyte_text() {
+    let result = parse_emmet("p{Привет, мир}+p{日本語}").unwrap();
+    assert_eq!(result[0].text, Some("Привет, мир".to_string()));
+    assert_eq!(result[1].text, Some("日本語".to_string()));
+}
+
+#[test]
+fn test_multibyte_class_and_id() {
+    let result = parse_emmet("div#über.café.ünïcødé").unwrap();
+    assert_eq!(result[0].id, Some("über".to_string()));
+    assert_eq!(result[0].classes, vec!["café", "ünïcødé"]);
+}
+
+#[test]
+fn test_multibyte_attribute_values() {
+    let result = parse_emmet("span[title=日本 data-x=\"héllo wörld\"]").unwrap();
+    assert_eq!(result[0].attributes[0].value, Some("日本".to_string()));
+    assert_eq!(
+        result[0].attributes[1].value,
+        Some("héllo wörld".to_string())
+    );
+}
+
+#[test]
+fn test_multibyte_html() {
+    assert_eq!(
+        emmet_to_html("ul>li.élément{ça va}*2").unwrap(),
+        "<ul><li class=\"élément\">ça va</li><li class=\"élément\">ça va</li></ul>"
+    );
+}
+
+#[test]
+fn test_multibyte_spans() {
+    let input = "p{Ωμέγα}+a.ünï[title=日本]";
+    let elements = EmmetParser::new(input).without_snippets().parse().unwrap();
+
+    let p = &elements[0];
+    assert_eq!(text(input, p.spans.text.unwrap()), "{Ωμέγα}");
+    assert_eq!(p.spans.text.unwrap().char_range(), 1..8);
+
+    let a = &elements[1];
+    let operator = a.spans.operator.unwrap();
+    assert_eq!(operator.char_range(), 8..9);
+    assert_eq!(operator.byte_range(), 13..14);
+    assert_eq!(text(input, a.spans.classes[0]), ".ünï");
+    assert_eq!(text(input, a.attributes[0].spans.value.unwrap()), "日本");
+    assert_eq!(a.span.char_end, input.chars().count());
+    assert_eq!(a.span.end, input.len());
+}
+
+#[test]
+fn test_multibyte_error_span() {
+    let input = "p{Привет}+@";
+    let err = parse_emmet(input).unwrap_err();
+    let parse_error = err.parse_error().unwrap();
+
+    assert_eq!(parse_error.code, ErrorCode::ExpectedElement);
+    assert_eq!(text(input, parse_error.span), "@");
+    assert_eq!(parse_error.span.char_start, 10);
+    assert_eq!(
+        parse_error.to_string(),
+        "expected element, found '@' at column 11"
+    );
+    assert!(err.render(input).ends_with("\n  |           ^"));
+}
+
+#[test]
+fn test_multibyte_unexpected_character() {
+    let input = "div[title=x]€";
+    let parse_error = parse_emmet(input)
+        .unwrap_err()
+        .parse_error()
+        .cloned()
+        .unwrap();
+    assert_eq!(parse_error.found, Some('€'));
+    assert_eq!(text(input, parse_error.span), "€");
+}
+
+#[test]
+fn test_long_abbreviation() {
+    // Scanning used to restart from the start of the input for every
+    // character, which made an abbreviation this long take minutes
+    let input = vec!["li.é{ü}"; 20_000].join("+");
+    let elements = parse_emmet(&input).unwrap();
+    assert_eq!(elements.len(), 20_000);
+    assert_eq!(elements[19_999].span.end, input.len());
+}
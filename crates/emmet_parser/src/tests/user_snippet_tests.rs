@@ -0,0 +1,216 @@
+use std::path::PathBuf;
+
+use crate::{EmmetError, EmmetParser, UserSnippets};
+
+const JSON: &str = r#"{
+  "html": {
+    "snippets": {
+      "card|crd": "div.card>div.card-body",
+      "bq": "blockquote.quote"
+    }
+  },
+  "css": {
+    "snippets": {
+      "mx": "margin: 0 auto"
+    }
+  },
+  "variables": {
+    "lang": "de"
+  }
+}"#;
+
+const TOML: &str = r#"
+[html.snippets]
+"card|crd" = "div.card>div.card-body"
+bq = "blockquote.quote"
+
+[css.snippets]
+mx = "margin: 0 auto"
+
+[variables]
+lang = "de"
+"#;
+
+fn expand(snippets: &UserSnippets, syntax: &str, input: &str) -> String {
+    let registry = snippets.registry(syntax);
+    let mut parser = EmmetParser::new(input).with_snippets(&registry);
+    parser
+        .parse()
+        .unwrap()
+        .iter()
+        .map(|element| element.to_html())
+        .collect()
+}
+
+fn location(err: EmmetError) -> (Option<PathBuf>, usize, usize) {
+    match err {
+        EmmetError::SnippetLoad {
+            file, line, column, ..
+        } => (file, line, column),
+        err => panic!("Expected SnippetLoad error, got {:?}", err),
+    }
+}
+
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("emmet_parser_{}_{}", std::process::id(), name));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn test_json_snippets() {
+    let snippets = UserSnippets::from_json(JSON).unwrap();
+
+    assert_eq!(
+        expand(&snippets, "html", "crd>p"),
+        "<div class=\"card\"><div class=\"card-body\"><p></p></div></div>"
+    );
+    assert_eq!(
+        snippets.syntax("css").unwrap().get("mx"),
+        Some("margin: 0 auto")
+    );
+    assert!(snippets.syntax("variables").is_none());
+}
+
+#[test]
+fn test_toml_snippets_match_json() {
+    let json = UserSnippets::from_json(JSON).unwrap();
+    let toml = UserSnippets::from_toml(TOML).unwrap();
+    assert_eq!(json, toml);
+}
+
+#[test]
+fn test_user_snippets_override_builtins() {
+    let snippets = UserSnippets::from_json(JSON).unwrap();
+
+    assert_eq!(
+        expand(&snippets, "html", "bq+btn:s"),
+        "<blockquote class=\"quote\"></blockquote><button type=\"submit\"></button>"
+    );
+}
+
+#[test]
+fn test_builtins_only_for_markup_syntaxes() {
+    let snippets = UserSnippets::from_json(JSON).unwrap();
+
+    assert!(snippets.registry("xhtml").contains("btn:s"));
+    assert!(!snippets.registry("xhtml").contains("crd"));
+    assert!(!snippets.registry("css").contains("btn:s"));
+    assert!(snippets.registry("css").contains("mx"));
+}
+
+#[test]
+fn test_later_merge_wins() {
+    let mut snippets = UserSnippets::from_json(JSON).unwrap();
+    let mut overrides = UserSnippets::new();
+    overrides.insert("html", "crd", "section.card");
+    snippets.merge(overrides);
+
+    let html = snippets.syntax("html").unwrap();
+    assert_eq!(html.get("crd"), Some("section.card"));
+    assert_eq!(html.get("card"), Some("div.card>div.card-body"));
+}
+
+#[test]
+fn test_user_variables() {
+    let snippets = UserSnippets::from_toml(TOML).unwrap();
+    assert_eq!(snippets.variable("lang"), Some("de"));
+
+    let config = snippets.config();
+    assert_eq!(config.variable("lang"), Some("de"));
+    assert_eq!(config.variable("charset"), Some("UTF-8"));
+
+    let registry = snippets.registry("html");
+    let mut parser = EmmetParser::new("doc")
+        .with_snippets(&registry)
+        .with_config(&config);
+    assert!(parser.parse().unwrap()[0]
+        .to_html()
+        .starts_with("<html lang=\"de\">"));
+
+    let err = UserSnippets::from_json("{\"variables\": {\"lang\": 1}}").unwrap_err();
+    assert_eq!(location(err), (None, 1, 16));
+}
+
+#[test]
+fn test_json_syntax_error_location() {
+    let err =
+        UserSnippets::from_json("{\n  \"html\": {\n    \"snippets\": {,}\n  }\n}").unwrap_err();
+    assert_eq!(location(err), (None, 3, 18));
+}
+
+#[test]
+fn test_invalid_snippet_location() {
+    let source = "{\n  \"html\": {\n    \"snippets\": {\n      \"ok\": \"p\",\n      \"broken\": \"ul>li[\"\n    }\n  }\n}";
+    let err = UserSnippets::from_json(source).unwrap_err();
+    assert!(err.to_string().contains("invalid snippet 'broken'"));
+    assert_eq!(location(err), (None, 5, 7));
+
+    let err =
+        UserSnippets::from_toml("[html.snippets]\nok = \"p\"\nbroken = \"div{\"\n").unwrap_err();
+    assert_eq!(location(err), (None, 3, 1));
+}
+
+#[test]
+fn test_non_string_snippet_location() {
+    let err = UserSnippets::from_json("{\"html\": {\"snippets\": {\"n\": 1}}}").unwrap_err();
+    assert_eq!(location(err), (None, 1, 24));
+}
+
+#[test]
+fn test_shape_errors_in_both_formats() {
+    let message = |err: EmmetError| match err {
+        EmmetError::SnippetLoad { message, .. } => message,
+        err => panic!("Expected SnippetLoad error, got {:?}", err),
+    };
+
+    let err = UserSnippets::from_json("[1]").unwrap_err();
+    assert_eq!(message(err), "expected an object of syntaxes");
+    let err = UserSnippets::from_json("{\"html\": {\"snippets\": 1}}").unwrap_err();
+    assert_eq!(message(err), "expected an object of snippets");
+    let err = UserSnippets::from_toml("[html]\nsnippets = 1\n").unwrap_err();
+    assert_eq!(message(err), "expected a table of snippets");
+
+    let err = UserSnippets::from_toml("variables = \"x\"\n").unwrap_err();
+    assert_eq!(message(err), "expected a table");
+    let err = UserSnippets::from_toml("[variables]\nlang = 1\n").unwrap_err();
+    assert_eq!(location(err), (None, 2, 1));
+}
+
+#[test]
+fn test_toml_syntax_error_location() {
+    let err = UserSnippets::from_toml("[html.snippets]\nbroken = \n").unwrap_err();
+    let (_, line, _) = location(err);
+    assert_eq!(line, 2);
+}
+
+#[test]
+fn test_load_from_files() {
+    let json = temp_file("snippets.json", JSON);
+    let toml = temp_file("snippets.toml", TOML);
+
+    assert_eq!(
+        UserSnippets::load(&json).unwrap(),
+        UserSnippets::load(&toml).unwrap()
+    );
+
+    std::fs::remove_file(json).unwrap();
+    std::fs::remove_file(toml).unwrap();
+}
+
+#[test]
+fn test_load_error_names_file() {
+    let path = temp_file("broken.toml", "[html.snippets]\nbroken = \"(p\"\n");
+    let err = UserSnippets::load(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(err
+        .to_string()
+        .starts_with(&format!("{}:2:1: ", path.display())));
+    assert_eq!(location(err), (Some(path), 2, 1));
+
+    let missing = std::env::temp_dir().join("emmet_parser_missing_snippets.json");
+    let (file, line, _) = location(UserSnippets::load(&missing).unwrap_err());
+    assert_eq!(file, Some(missing));
+    assert_eq!(line, 0);
+}
@@ -0,0 +1,77 @@
+use crate::{parse_emmet_with_config, EmmetError, ExpandConfig};
+
+fn expand_with(config: &ExpandConfig, input: &str) -> Result<String, EmmetError> {
+    let elements = parse_emmet_with_config(input, config)?;
+    Ok(elements.iter().map(|element| element.to_html()).collect())
+}
+
+#[test]
+fn test_default_variables() {
+    let config = ExpandConfig::default();
+    assert_eq!(config.variable("lang"), Some("en"));
+    assert_eq!(config.variable("charset"), Some("UTF-8"));
+    assert_eq!(config.variable("locale"), Some("en-US"));
+
+    assert_eq!(
+        expand_with(&config, "p[lang=${lang}]{${locale}}").unwrap(),
+        "<p lang=\"en\">en-US</p>"
+    );
+}
+
+#[test]
+fn test_variables_in_snippets() {
+    let config = ExpandConfig::new()
+        .with_variable("lang", "de")
+        .with_variable("charset", "ISO-8859-1");
+    let html = expand_with(&config, "!").unwrap();
+
+    assert!(html.contains("<html lang=\"de\">"));
+    assert!(html.contains("<meta charset=\"ISO-8859-1\" />"));
+}
+
+#[test]
+fn test_variables_in_quoted_values_and_placeholders() {
+    let config = ExpandConfig::new().with_variable("site", "Example");
+
+    assert_eq!(
+        expand_with(&config, "span[title=\"About ${site}\"]{${1:${site}}}").unwrap(),
+        "<span title=\"About Example\">Example</span>"
+    );
+}
+
+#[test]
+fn test_fields_are_not_variables() {
+    let config = ExpandConfig::new().strict(true);
+
+    assert_eq!(
+        expand_with(&config, "data[value=${1:name}]").unwrap(),
+        "<data value=\"name\"></data>"
+    );
+}
+
+#[test]
+fn test_unknown_variables() {
+    assert_eq!(
+        expand_with(&ExpandConfig::default(), "p{${missing}}").unwrap(),
+        "<p>${missing}</p>"
+    );
+
+    let strict = ExpandConfig::new().strict(true);
+    assert!(matches!(
+        expand_with(&strict, "p{${missing}}"),
+        Err(EmmetError::UnknownVariable(name)) if name == "missing"
+    ));
+    assert!(matches!(
+        expand_with(&strict, "div[data-x=${missing}]"),
+        Err(EmmetError::UnknownVariable(_))
+    ));
+}
+
+#[test]
+fn test_escaped_variables_are_kept() {
+    let config = ExpandConfig::default();
+    assert_eq!(
+        expand_with(&config, "p[title=\"\\${lang}\"]{\\${locale} ${lang}}").unwrap(),
+        "<p title=\"${lang}\">${locale} en</p>"
+    );
+}
@@ -0,0 +1,157 @@
+use crate::{parse_emmet, Attribute, EmmetElement, Fold, Renderer, Visitor, VisitorMut};
+
+#[derive(Default)]
+struct Counter {
+    elements: Vec<String>,
+    groups: usize,
+    texts: Vec<String>,
+    attributes: Vec<String>,
+}
+
+impl Visitor for Counter {
+    fn visit_element(&mut self, element: &EmmetElement) {
+        self.elements.push(element.tag.clone());
+        self.walk_element(element);
+    }
+
+    fn visit_group(&mut self, group: &EmmetElement) {
+        self.groups += 1;
+        self.walk_element(group);
+    }
+
+    fn visit_text(&mut self, text: &EmmetElement) {
+        self.texts.extend(text.text.clone());
+        self.walk_element(text);
+    }
+
+    fn visit_attribute(&mut self, attribute: &Attribute) {
+        self.attributes.push(attribute.name.clone());
+    }
+}
+
+#[test]
+fn test_visitor_reaches_every_node_kind() {
+    let elements = parse_emmet("div[title=x]>(p[lang=en]>{Hi})*2+{Bye}^span").unwrap();
+    let mut counter = Counter::default();
+    counter.visit_nodes(&elements);
+
+    assert_eq!(counter.elements, ["div", "p", "span"]);
+    assert_eq!(counter.groups, 1);
+    assert_eq!(counter.texts, ["Hi", "Bye"]);
+    assert_eq!(counter.attributes, ["title", "lang"]);
+}
+
+#[test]
+fn test_default_methods_reach_nodes_under_text() {
+    struct Ids(Vec<String>);
+
+    impl Visitor for Ids {
+        fn visit_element(&mut self, element: &EmmetElement) {
+            self.0.extend(element.id.clone());
+            self.walk_element(element);
+        }
+    }
+
+    impl VisitorMut for Ids {
+        fn visit_element_mut(&mut self, element: &mut EmmetElement) {
+            element.classes.push("y".to_string());
+            self.walk_element_mut(element);
+        }
+    }
+
+    impl Fold for Ids {
+        fn fold_element(&mut self, mut element: EmmetElement) -> EmmetElement {
+            element.tag = element.tag.to_uppercase();
+            self.walk_fold(element)
+        }
+    }
+
+    let mut elements = parse_emmet("div>{a}>p#x").unwrap();
+    let mut ids = Ids(Vec::new());
+    ids.visit_nodes(&elements);
+    assert_eq!(ids.0, ["x"]);
+
+    ids.visit_nodes_mut(&mut elements);
+    let elements = ids.fold_nodes(elements);
+    assert_eq!(
+        Renderer::new().render(&elements),
+        "<DIV class=\"y\">a<P id=\"x\" class=\"y\"></P></DIV>"
+    );
+}
+
+#[test]
+fn test_visitor_can_stop_descending() {
+    struct TopLevel(Vec<String>);
+
+    impl Visitor for TopLevel {
+        fn visit_element(&mut self, element: &EmmetElement) {
+            self.0.push(element.tag.clone());
+        }
+    }
+
+    let mut visitor = TopLevel(Vec::new());
+    visitor.visit_nodes(&parse_emmet("ul>li+li^p").unwrap());
+    assert_eq!(visitor.0, ["ul", "p"]);
+}
+
+#[test]
+fn test_visitor_mut_rewrites_attributes() {
+    struct Https;
+
+    impl VisitorMut for Https {
+        fn visit_attribute_mut(&mut self, attribute: &mut Attribute) {
+            if let Some(value) = &mut attribute.value {
+                *value = value.replace("http:", "https:");
+            }
+        }
+    }
+
+    let mut elements = parse_emmet("nav>a:link+(a[href='http://x'])").unwrap();
+    Https.visit_nodes_mut(&mut elements);
+    assert_eq!(
+        Renderer::new().render(&elements),
+        "<nav><a href=\"https://\"></a><a href=\"https://x\"></a></nav>"
+    );
+}
+
+#[test]
+fn test_fold_replaces_and_removes_nodes() {
+    /// Unwraps groups into their children and drops `data-*` attributes.
+    struct Flatten;
+
+    impl Fold for Flatten {
+        fn fold_nodes(&mut self, nodes: Vec<EmmetElement>) -> Vec<EmmetElement> {
+            let mut folded = Vec::new();
+            for node in nodes {
+                let node = self.fold_node(node);
+                if node.is_group() && node.multiplier.is_none() {
+                    folded.extend(node.children);
+                } else {
+                    folded.push(node);
+                }
+            }
+            folded
+        }
+
+        fn fold_attributes(&mut self, attributes: Vec<Attribute>) -> Vec<Attribute> {
+            attributes
+                .into_iter()
+                .filter(|attribute| !attribute.name.starts_with("data-"))
+                .collect()
+        }
+
+        fn fold_text(&mut self, mut text: EmmetElement) -> EmmetElement {
+            text.text = text.text.map(|t| t.to_uppercase());
+            text
+        }
+    }
+
+    let elements = Flatten.fold_nodes(parse_emmet("div>(p[data-x=1 id=a]+(b>{hi}))").unwrap());
+    let div = &elements[0];
+    assert_eq!(div.children.len(), 2);
+    assert_eq!(div.children[0].attributes.len(), 1);
+    assert_eq!(
+        Renderer::new().render(&elements),
+        "<div><p id=\"a\"></p><b>HI</b></div>"
+    );
+}
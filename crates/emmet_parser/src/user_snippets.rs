@@ -0,0 +1,369 @@
+// Without a file format, only snippets inserted directly are used
+#![cfg_attr(not(any(feature = "json", feature = "toml")), allow(dead_code))]
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::{EmmetError, EmmetParser, ExpandConfig, SnippetRegistry};
+
+/// Syntaxes that start from the built-in HTML snippets.
+const HTML_SYNTAXES: &[&str] = &["html", "xhtml", "jsx"];
+
+/// Top-level key of a snippet file holding variables rather than a syntax.
+const VARIABLES_KEY: &str = "variables";
+
+/// User snippets grouped by syntax, loaded from a VS Code-compatible
+/// `snippets.json` or its TOML equivalent:
+///
+/// ```json
+/// {
+///   "html": {
+///     "snippets": {
+///       "card|crd": "div.card>div.card-body"
+///     }
+///   },
+///   "variables": {
+///     "lang": "de"
+///   }
+/// }
+/// ```
+///
+/// ```toml
+/// [html.snippets]
+/// "card|crd" = "div.card>div.card-body"
+///
+/// [variables]
+/// lang = "de"
+/// ```
+///
+/// Other members of a syntax section are ignored. Reading JSON and TOML
+/// needs the `json` and `toml` features, both on by default.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserSnippets {
+    syntaxes: HashMap<String, SnippetRegistry>,
+    variables: HashMap<String, String>,
+}
+
+impl UserSnippets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads snippets from a `.json` or `.toml` file.
+    #[cfg(any(feature = "json", feature = "toml"))]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, EmmetError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|err| EmmetError::SnippetLoad {
+            file: Some(path.to_path_buf()),
+            line: 0,
+            column: 0,
+            message: err.to_string(),
+        })?;
+
+        let is_toml = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+        let format = if is_toml { "toml" } else { "json" };
+        let result = match format {
+            #[cfg(feature = "toml")]
+            "toml" => Self::from_toml(&source),
+            #[cfg(feature = "json")]
+            "json" => Self::from_json(&source),
+            _ => Err(EmmetError::SnippetLoad {
+                file: None,
+                line: 0,
+                column: 0,
+                message: format!("reading this file needs the `{}` feature", format),
+            }),
+        };
+
+        result.map_err(|err| with_file(err, path))
+    }
+
+    /// Parses snippets in the `snippets.json` format.
+    #[cfg(feature = "json")]
+    pub fn from_json(source: &str) -> Result<Self, EmmetError> {
+        let value: serde_json::Value =
+            serde_json::from_str(source).map_err(|err| EmmetError::SnippetLoad {
+                file: None,
+                line: err.line(),
+                column: err.column(),
+                message: err.to_string(),
+            })?;
+
+        Self::from_value(source, value.into(), "an object")
+    }
+
+    /// Parses snippets in the TOML equivalent of `snippets.json`.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(source: &str) -> Result<Self, EmmetError> {
+        let syntaxes: toml::Table = toml::from_str(source).map_err(|err| {
+            let (line, column) = err
+                .span()
+                .map_or((0, 0), |span| line_column(source, span.start));
+            EmmetError::SnippetLoad {
+                file: None,
+                line,
+                column,
+                message: err.message().to_string(),
+            }
+        })?;
+
+        Self::from_value(source, toml::Value::Table(syntaxes).into(), "a table")
+    }
+
+    /// Checks and registers the syntaxes and variables of a snippet file
+    /// read from `source`. Errors call objects or tables `table`.
+    fn from_value(source: &str, value: Value, table: &str) -> Result<Self, EmmetError> {
+        let Value::Table(syntaxes) = value else {
+            let message = format!("expected {} of syntaxes", table);
+            return Err(load_error(source, &[], &message));
+        };
+
+        let mut snippets = Self::new();
+        for (syntax, section) in syntaxes {
+            let Value::Table(section) = section else {
+                let message = format!("expected {}", table);
+                return Err(load_error(source, &[&syntax], &message));
+            };
+            if syntax == VARIABLES_KEY {
+                for (name, value) in section {
+                    let Value::String(value) = value else {
+                        return Err(load_error(
+                            source,
+                            &[&syntax, &name],
+                            "variable must be a string",
+                        ));
+                    };
+                    snippets.set_variable(&name, &value);
+                }
+                continue;
+            }
+            let Some((_, entries)) = section.into_iter().find(|(key, _)| key == "snippets") else {
+                continue;
+            };
+            let Value::Table(entries) = entries else {
+                let message = format!("expected {} of snippets", table);
+                return Err(load_error(source, &[&syntax, "snippets"], &message));
+            };
+
+            for (names, abbreviation) in entries {
+                let Value::String(abbreviation) = abbreviation else {
+                    return Err(load_error(
+                        source,
+                        &[&syntax, "snippets", &names],
+                        "snippet must be a string",
+                    ));
+                };
+                snippets.insert_checked(source, &syntax, &names, &abbreviation)?;
+            }
+        }
+
+        Ok(snippets)
+    }
+
+    /// Validates a markup snippet's abbreviation before registering it.
+    /// Snippets of other syntaxes are registered as written.
+    fn insert_checked(
+        &mut self,
+        source: &str,
+        syntax: &str,
+        names: &str,
+        abbreviation: &str,
+    ) -> Result<(), EmmetError> {
+        let parsed = if HTML_SYNTAXES.contains(&syntax) {
+            EmmetParser::new(abbreviation).without_snippets().parse()
+        } else {
+            Ok(Vec::new())
+        };
+        if let Err(err) = parsed {
+            return Err(load_error(
+                source,
+                &[syntax, "snippets", names],
+                &format!("invalid snippet '{}': {}", names, err),
+            ));
+        }
+
+        self.insert(syntax, names, abbreviation);
+        Ok(())
+    }
+
+    /// Registers a snippet for `syntax` under every `|`-separated alias.
+    pub fn insert(&mut self, syntax: &str, names: &str, abbreviation: &str) {
+        self.syntaxes
+            .entry(syntax.to_string())
+            .or_default()
+            .insert(names, abbreviation);
+    }
+
+    /// Sets a variable, replacing any existing value.
+    pub fn set_variable(&mut self, name: &str, value: &str) {
+        self.variables.insert(name.to_string(), value.to_string());
+    }
+
+    /// Adds the snippets and variables of `other`, replacing those with the
+    /// same name and syntax. Files merged later therefore take precedence.
+    pub fn merge(&mut self, other: UserSnippets) {
+        for (syntax, registry) in other.syntaxes {
+            self.syntaxes.entry(syntax).or_default().extend(&registry);
+        }
+        self.variables.extend(other.variables);
+    }
+
+    pub fn variable(&self, name: &str) -> Option<&str> {
+        self.variables.get(name).map(String::as_str)
+    }
+
+    /// Builds an expansion configuration with the default variables,
+    /// overridden by the user's.
+    pub fn config(&self) -> ExpandConfig {
+        ExpandConfig::new().with_variables(
+            self.variables
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str())),
+        )
+    }
+
+    /// The user snippets declared for `syntax` alone.
+    pub fn syntax(&self, syntax: &str) -> Option<&SnippetRegistry> {
+        self.syntaxes.get(syntax)
+    }
+
+    /// Names of the syntaxes that declare snippets.
+    pub fn syntaxes(&self) -> impl Iterator<Item = &str> {
+        self.syntaxes.keys().map(String::as_str)
+    }
+
+    /// Builds the registry used to expand abbreviations of `syntax`: the
+    /// built-in HTML snippets for markup syntaxes (`html`, `xhtml`, `jsx`),
+    /// overridden by user snippets with the same name.
+    pub fn registry(&self, syntax: &str) -> SnippetRegistry {
+        let mut registry = if HTML_SYNTAXES.contains(&syntax) {
+            SnippetRegistry::html()
+        } else {
+            SnippetRegistry::new()
+        };
+
+        if let Some(user) = self.syntaxes.get(syntax) {
+            registry.extend(user);
+        }
+
+        registry
+    }
+}
+
+/// A value of a snippet file, read from JSON or TOML so that both formats
+/// are checked and registered the same way.
+enum Value {
+    String(String),
+    /// An object or table.
+    Table(Vec<(String, Value)>),
+    Other,
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Value> for Value {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::String(value) => Value::String(value),
+            serde_json::Value::Object(entries) => Value::Table(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            ),
+            _ => Value::Other,
+        }
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::Value> for Value {
+    fn from(value: toml::Value) -> Self {
+        match value {
+            toml::Value::String(value) => Value::String(value),
+            toml::Value::Table(entries) => Value::Table(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            ),
+            _ => Value::Other,
+        }
+    }
+}
+
+fn with_file(err: EmmetError, path: &Path) -> EmmetError {
+    match err {
+        EmmetError::SnippetLoad {
+            file: None,
+            line,
+            column,
+            message,
+        } => EmmetError::SnippetLoad {
+            file: Some(PathBuf::from(path)),
+            line,
+            column,
+            message,
+        },
+        err => err,
+    }
+}
+
+/// Builds an error located at the last key of `path` in `source`.
+fn load_error(source: &str, path: &[&str], message: &str) -> EmmetError {
+    let (line, column) = line_column(source, locate(source, path));
+    EmmetError::SnippetLoad {
+        file: None,
+        line,
+        column,
+        message: message.to_string(),
+    }
+}
+
+/// Finds the byte offset of the last key in `path` by searching for each key
+/// in turn, quoted or bare, after the previous one. Falls back to the last
+/// key found.
+fn locate(source: &str, path: &[&str]) -> usize {
+    let mut offset = 0;
+
+    for key in path {
+        let quoted = format!("\"{}\"", key);
+        let found = source[offset..]
+            .find(&quoted)
+            .or_else(|| find_bare_key(&source[offset..], key));
+        match found {
+            Some(position) => offset += position,
+            None => break,
+        }
+    }
+
+    offset
+}
+
+/// Finds `key` written as a bare TOML key, not as part of a longer word.
+fn find_bare_key(source: &str, key: &str) -> Option<usize> {
+    let is_key_char = |ch: char| ch.is_alphanumeric() || ch == '_' || ch == '-';
+
+    source
+        .match_indices(key)
+        .map(|(position, _)| position)
+        .find(|&position| {
+            let before = source[..position].chars().next_back();
+            let after = source[position + key.len()..].chars().next();
+            !before.is_some_and(is_key_char) && !after.is_some_and(is_key_char)
+        })
+}
+
+/// Converts a byte offset to a 1-based line and column.
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before, |newline| &before[newline + 1..])
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+/// Version of the serialized shape of elements, attributes, spans and
+/// errors. It is increased when a field is removed or renamed or changes
+/// meaning; adding a field, a node kind such as `doctype` or an error code
+/// keeps it, and readers that predate the addition reject data using it.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Serialized data together with the [`FORMAT_VERSION`] of its shape, to
+/// exchange elements and errors with other programs. Deserializing data of
+/// another version fails.
+///
+/// ```
+/// use emmet_parser::{parse_emmet, EmmetElement, Versioned};
+///
+/// let elements = parse_emmet("ul>li.item").unwrap();
+/// let json = serde_json::to_string(&Versioned::new(&elements)).unwrap();
+/// assert!(json.starts_with(r#"{"version":1,"data":[{"kind":"element","tag":"ul""#));
+///
+/// let parsed: Versioned<Vec<EmmetElement>> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(parsed.data, elements);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Unchecked<T>")]
+pub struct Versioned<T> {
+    pub version: u32,
+    pub data: T,
+}
+
+impl<T> Versioned<T> {
+    /// Wraps `data` with the current format version.
+    pub fn new(data: T) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            data,
+        }
+    }
+}
+
+/// [`Versioned`] data before its version is checked.
+#[derive(Deserialize)]
+struct Unchecked<T> {
+    version: u32,
+    data: T,
+}
+
+impl<T> TryFrom<Unchecked<T>> for Versioned<T> {
+    type Error = String;
+
+    fn try_from(unchecked: Unchecked<T>) -> Result<Self, String> {
+        if unchecked.version != FORMAT_VERSION {
+            return Err(format!(
+                "unsupported format version {}, expected {}",
+                unchecked.version, FORMAT_VERSION
+            ));
+        }
+        Ok(Self {
+            version: unchecked.version,
+            data: unchecked.data,
+        })
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{Attribute, ElementKind, EmmetElement};
+
+/// Walks a tree of parsed elements, for passes that inspect it such as
+/// linters.
+///
+/// Every method has a default: nodes are dispatched by kind to
+/// `visit_element`, `visit_group` or `visit_text`, which also gets the
+/// comments and doctypes of snippets. Each goes on to its attributes and
+/// children with [`walk_element`](Visitor::walk_element), including the
+/// nodes written after text, as in `{Hi}>p`. Override the methods for the
+/// nodes of interest, calling `walk_element` to keep descending.
+///
+/// ```
+/// use emmet_parser::{parse_emmet, EmmetElement, Visitor};
+///
+/// #[derive(Default)]
+/// struct Ids(Vec<String>);
+///
+/// impl Visitor for Ids {
+///     fn visit_element(&mut self, element: &EmmetElement) {
+///         self.0.extend(element.id.clone());
+///         self.walk_element(element);
+///     }
+/// }
+///
+/// let mut ids = Ids::default();
+/// ids.visit_nodes(&parse_emmet("#page>(header#top+main#content)").unwrap());
+/// assert_eq!(ids.0, ["page", "top", "content"]);
+/// ```
+pub trait Visitor {
+    fn visit_nodes(&mut self, nodes: &[EmmetElement]) {
+        for node in nodes {
+            self.visit_node(node);
+        }
+    }
+
+    fn visit_node(&mut self, node: &EmmetElement) {
+        match node.kind {
+            ElementKind::Element => self.visit_element(node),
+            ElementKind::Group => self.visit_group(node),
+            ElementKind::Text | ElementKind::Comment | ElementKind::Doctype => {
+                self.visit_text(node)
+            }
+        }
+    }
+
+    fn visit_element(&mut self, element: &EmmetElement) {
+        self.walk_element(element);
+    }
+
+    fn visit_group(&mut self, group: &EmmetElement) {
+        self.walk_element(group);
+    }
+
+    fn visit_text(&mut self, text: &EmmetElement) {
+        self.walk_element(text);
+    }
+
+    fn visit_attribute(&mut self, _attribute: &Attribute) {}
+
+    /// Visits the attributes and then the children of `element`.
+    fn walk_element(&mut self, element: &EmmetElement) {
+        for attribute in &element.attributes {
+            self.visit_attribute(attribute);
+        }
+        self.visit_nodes(&element.children);
+    }
+}
+
+/// Walks a tree of parsed elements to change it in place, for passes such
+/// as adding classes or rewriting attributes. The methods mirror those of
+/// [`Visitor`].
+///
+/// ```
+/// use emmet_parser::{parse_emmet, EmmetElement, Renderer, VisitorMut};
+///
+/// struct AddClass(&'static str);
+///
+/// impl VisitorMut for AddClass {
+///     fn visit_element_mut(&mut self, element: &mut EmmetElement) {
+///         element.classes.push(self.0.to_string());
+///         self.walk_element_mut(element);
+///     }
+/// }
+///
+/// let mut elements = parse_emmet("ul>li*2").unwrap();
+/// AddClass("x").visit_nodes_mut(&mut elements);
+/// assert_eq!(
+///     Renderer::new().render(&elements),
+///     "<ul class=\"x\"><li class=\"x\"></li><li class=\"x\"></li></ul>"
+/// );
+/// ```
+pub trait VisitorMut {
+    fn visit_nodes_mut(&mut self, nodes: &mut [EmmetElement]) {
+        for node in nodes {
+            self.visit_node_mut(node);
+        }
+    }
+
+    fn visit_node_mut(&mut self, node: &mut EmmetElement) {
+        match node.kind {
+            ElementKind::Element => self.visit_element_mut(node),
+            ElementKind::Group => self.visit_group_mut(node),
+            ElementKind::Text | ElementKind::Comment | ElementKind::Doctype => {
+                self.visit_text_mut(node)
+            }
+        }
+    }
+
+    fn visit_element_mut(&mut self, element: &mut EmmetElement) {
+        self.walk_element_mut(element);
+    }
+
+    fn visit_group_mut(&mut self, group: &mut EmmetElement) {
+        self.walk_element_mut(group);
+    }
+
+    fn visit_text_mut(&mut self, text: &mut EmmetElement) {
+        self.walk_element_mut(text);
+    }
+
+    fn visit_attribute_mut(&mut self, _attribute: &mut Attribute) {}
+
+    /// Visits the attributes and then the children of `element`.
+    fn walk_element_mut(&mut self, element: &mut EmmetElement) {
+        for attribute in &mut element.attributes {
+            self.visit_attribute_mut(attribute);
+        }
+        self.visit_nodes_mut(&mut element.children);
+    }
+}
+
+/// Rebuilds a tree of parsed elements, for passes that replace, add or
+/// remove nodes. Each method takes a node by value and returns what takes
+/// its place; lists of nodes and attributes go through `fold_nodes` and
+/// `fold_attributes`, which can drop or insert entries.
+///
+/// ```
+/// use emmet_parser::{parse_emmet, EmmetElement, Fold, Renderer};
+///
+/// /// Removes bare text nodes.
+/// struct StripText;
+///
+/// impl Fold for StripText {
+///     fn fold_nodes(&mut self, nodes: Vec<EmmetElement>) -> Vec<EmmetElement> {
+///         nodes
+///             .into_iter()
+///             .filter(|node| !node.is_text())
+///             .map(|node| self.fold_node(node))
+///             .collect()
+///     }
+/// }
+///
+/// let elements = StripText.fold_nodes(parse_emmet("p>{Hi}+b").unwrap());
+/// assert_eq!(Renderer::new().render(&elements), "<p><b></b></p>");
+/// ```
+pub trait Fold {
+    fn fold_nodes(&mut self, nodes: Vec<EmmetElement>) -> Vec<EmmetElement> {
+        nodes.into_iter().map(|node| self.fold_node(node)).collect()
+    }
+
+    fn fold_node(&mut self, node: EmmetElement) -> EmmetElement {
+        match node.kind {
+            ElementKind::Element => self.fold_element(node),
+            ElementKind::Group => self.fold_group(node),
+            ElementKind::Text | ElementKind::Comment | ElementKind::Doctype => self.fold_text(node),
+        }
+    }
+
+    fn fold_element(&mut self, element: EmmetElement) -> EmmetElement {
+        self.walk_fold(element)
+    }
+
+    fn fold_group(&mut self, group: EmmetElement) -> EmmetElement {
+        self.walk_fold(group)
+    }
+
+    fn fold_text(&mut self, text: EmmetElement) -> EmmetElement {
+        self.walk_fold(text)
+    }
+
+    fn fold_attributes(&mut self, attributes: Vec<Attribute>) -> Vec<Attribute> {
+        attributes
+            .into_iter()
+            .map(|attribute| self.fold_attribute(attribute))
+            .collect()
+    }
+
+    fn fold_attribute(&mut self, attribute: Attribute) -> Attribute {
+        attribute
+    }
+
+    /// Folds the attributes and then the children of `element`.
+    fn walk_fold(&mut self, mut element: EmmetElement) -> EmmetElement {
+        element.attributes = self.fold_attributes(std::mem::take(&mut element.attributes));
+        element.children = self.fold_nodes(std::mem::take(&mut element.children));
+        element
+    }
+}